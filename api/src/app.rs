@@ -0,0 +1,205 @@
+use axum::{
+    body::Body, http::Request, middleware::from_fn, response::IntoResponse, routing::Route, Router,
+    Server,
+};
+use sea_orm::DbConn;
+use std::{convert::Infallible, sync::Arc};
+use tokio::{signal, sync::oneshot, time::timeout};
+use tower::{Layer, Service};
+
+use crate::config::{AppConfig, ConfigError};
+use crate::middlewares::request_id;
+use crate::routes::{
+    auth_routes, brand_routes, cart_routes, category_routes, docs_routes, health_routes,
+    order_routes, product_routes, upload_routes,
+};
+use crate::services::{ReservationService, SoftDeleteService};
+use crate::storage::{self, Storage};
+
+#[derive(Debug, Clone)]
+pub struct AppState {
+    pub(crate) conn: DbConn,
+    pub(crate) config: Arc<AppConfig>,
+    pub(crate) storage: Arc<dyn Storage>,
+}
+
+impl AppState {
+    // Starts from the default settings, use with_config to pass a loaded AppConfig;
+    pub fn new(conn: DbConn) -> Self {
+        let config = AppConfig::default();
+
+        // The default storage backend is the local one;
+        Self {
+            conn,
+            storage: storage::local(&config.storage),
+            config: Arc::new(config),
+        }
+    }
+
+    // Also rebuilds the storage backend from the config's storage section;
+    // The config is validated here, so building the app from this state cannot fail on it;
+    pub fn with_config(mut self, config: AppConfig) -> Result<Self, ConfigError> {
+        config.validate_app()?;
+
+        self.storage = storage::from_config(&config.storage)
+            .map_err(|e| ConfigError::InvalidValue("storage", e.to_string()))?;
+        self.config = Arc::new(config);
+        Ok(self)
+    }
+
+    // Plugs in a Storage implementation of the embedding binary's own;
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn conn(&self) -> &DbConn {
+        &self.conn
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    pub fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
+    }
+}
+
+// Every route of the API with the CORS layer from the state's config, ready to be served or nested;
+pub fn build_router(state: AppState) -> Router {
+    AppBuilder::new(state).build()
+}
+
+// Lets an embedding binary add its own routes and layers on top of the API before serving it;
+// Like axum's Router::layer, a layer only wraps the routes merged before it;
+pub struct AppBuilder {
+    state: AppState,
+    router: Router<AppState>,
+}
+
+impl AppBuilder {
+    pub fn new(state: AppState) -> Self {
+        let router = Router::new()
+            .merge(auth_routes())
+            .merge(category_routes())
+            .merge(brand_routes())
+            .merge(product_routes())
+            .merge(cart_routes())
+            .merge(order_routes())
+            .merge(health_routes())
+            .merge(docs_routes())
+            .merge(upload_routes(&state.config.storage));
+
+        Self { state, router }
+    }
+
+    pub fn merge(mut self, router: Router<AppState>) -> Self {
+        self.router = self.router.merge(router);
+        self
+    }
+
+    pub fn nest(mut self, path: &str, router: Router<AppState>) -> Self {
+        self.router = self.router.nest(path, router);
+        self
+    }
+
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route<Body>> + Clone + Send + 'static,
+        L::Service: Service<Request<Body>> + Clone + Send + 'static,
+        <L::Service as Service<Request<Body>>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request<Body>>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request<Body>>>::Future: Send + 'static,
+    {
+        self.router = self.router.layer(layer);
+        self
+    }
+
+    pub fn build(self) -> Router {
+        // CORS goes outermost so preflight requests are answered for the extra routes too,
+        // the request id wraps everything else so every error body can carry it;
+        let cors = self
+            .state
+            .config
+            .cors_layer()
+            .expect("Either the default or a validated config, see AppState::with_config");
+
+        self.router
+            .with_state(self.state)
+            .layer(from_fn(request_id))
+            .layer(cors)
+    }
+
+    // Binds the configured address and serves until SIGINT/SIGTERM,
+    // the expired reservation sweeper and the soft-delete purger run alongside the server and stop with it;
+    pub async fn serve(self) -> Result<(), hyper::Error> {
+        let addr = self.state.config.socket_addr();
+        let shutdown_timeout = self.state.config.shutdown_timeout();
+        let sweeper = ReservationService::spawn_sweeper(self.state.conn.clone());
+        let purger = self.state.config.purge_retention().map(|retention| {
+            SoftDeleteService::spawn_purger(
+                self.state.conn.clone(),
+                self.state.storage.clone(),
+                retention,
+            )
+        });
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        let server = Server::bind(&addr)
+            .serve(self.build().into_make_service())
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            });
+        tokio::pin!(server);
+
+        // Once a signal arrives the server stops accepting connections,
+        // in-flight requests get `shutdown_timeout` to finish before we give up on them;
+        let result = tokio::select! {
+            result = &mut server => result,
+            _ = shutdown_signal() => {
+                let _ = shutdown_tx.send(());
+
+                match timeout(shutdown_timeout, server).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        tracing::warn!(timeout = ?shutdown_timeout, "Timed out waiting for in-flight requests to finish");
+                        Ok(())
+                    }
+                }
+            }
+        };
+
+        sweeper.abort();
+        if let Some(purger) = purger {
+            purger.abort();
+        }
+
+        result
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,609 @@
+use axum::http::{header::HeaderName, HeaderValue, Method, Uri};
+use chrono::Duration;
+use sea_orm::ConnectOptions;
+use serde::Deserialize;
+use std::{
+    env, fs,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+    time::Duration as StdDuration,
+};
+use thiserror::Error;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+use tracing_subscriber::EnvFilter;
+
+use crate::middlewares::REQUEST_ID_HEADER;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    ReadFile(String, std::io::Error),
+    #[error("Failed to parse config file {0}: {1}")]
+    ParseFile(String, toml::de::Error),
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(&'static str, String),
+}
+
+// 1. Every setting has a default, so the TOML file (APP_CONFIG_FILE) only needs the values that differ;
+// 2. Environment variables (see apply_env) are applied last and win over the file;
+// 3. Everything is validated once at startup, so the rest of the app can rely on the values;
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub cors: CorsConfig,
+    pub auth: AuthConfig,
+    pub inventory: InventoryConfig,
+    pub catalog: CatalogConfig,
+    pub storage: StorageConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    pub log_filter: String,
+    pub shutdown_timeout_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+}
+
+// A single "*" entry allows any origin/method/header;
+// No origin is allowed until some are configured, browsers then refuse every cross-origin request;
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+    pub bcrypt_cost: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct InventoryConfig {
+    pub reservation_ttl_minutes: i64,
+}
+
+// Soft-deleted brands, categories and products are purged once deleted for that long, 0 keeps them forever;
+// price_facet_bounds split product listings into price buckets, in minor units and ascending:
+// [1000, 5000] gives the buckets below 1000, 1000 to 4999 and 5000 or more;
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CatalogConfig {
+    pub purge_deleted_after_days: i64,
+    pub brand_delete_policy: CascadePolicy,
+    pub category_delete_policy: CascadePolicy,
+    pub price_facet_bounds: Vec<i64>,
+}
+
+// What deleting a brand or category does to its active products:
+// 1. restrict refuses the delete while any exists;
+// 2. cascade soft-deletes them along with it, restoring it brings them back;
+// 3. detach leaves them on sale, they are listed without the brand/category name until it is restored;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CascadePolicy {
+    #[default]
+    Restrict,
+    Cascade,
+    Detach,
+}
+
+impl FromStr for CascadePolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "restrict" => Ok(Self::Restrict),
+            "cascade" => Ok(Self::Cascade),
+            "detach" => Ok(Self::Detach),
+            _ => Err(()),
+        }
+    }
+}
+
+// Where uploaded product images go, see storage/;
+// public_url prefixes the object keys in the URLs handed to clients, left empty it defaults to
+// the /uploads route for local storage and to <endpoint>/<bucket> for S3;
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    pub local_dir: String,
+    pub public_url: String,
+    pub max_image_bytes: usize,
+    pub thumbnail_size: u32,
+    pub s3: S3Config,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Local,
+    S3,
+}
+
+impl FromStr for StorageBackend {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local" => Ok(Self::Local),
+            "s3" => Ok(Self::S3),
+            _ => Err(()),
+        }
+    }
+}
+
+// Any S3-compatible service (AWS, MinIO, R2...), objects are addressed path-style as <endpoint>/<bucket>/<key>;
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 6969,
+            log_filter: "info".to_string(),
+            shutdown_timeout_seconds: 30,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 10,
+            min_connections: 1,
+        }
+    }
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "DELETE", "PATCH"]
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+            allowed_headers: vec!["content-type".to_string(), "authorization".to_string()],
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            access_token_ttl_minutes: 15,
+            refresh_token_ttl_days: 30,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl Default for InventoryConfig {
+    fn default() -> Self {
+        Self {
+            reservation_ttl_minutes: 15,
+        }
+    }
+}
+
+impl Default for CatalogConfig {
+    fn default() -> Self {
+        Self {
+            purge_deleted_after_days: 0,
+            brand_delete_policy: CascadePolicy::default(),
+            category_delete_policy: CascadePolicy::default(),
+            price_facet_bounds: vec![1000, 2500, 5000, 10000],
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::Local,
+            local_dir: "uploads".to_string(),
+            public_url: String::new(),
+            max_image_bytes: 5 * 1024 * 1024,
+            thumbnail_size: 256,
+            s3: S3Config::default(),
+        }
+    }
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env::var("APP_CONFIG_FILE") {
+            Ok(path) => Self::from_file(&path)?,
+            Err(_) => Self::default(),
+        };
+
+        config.apply_env()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let content =
+            fs::read_to_string(path).map_err(|e| ConfigError::ReadFile(path.to_string(), e))?;
+
+        toml::from_str(&content).map_err(|e| ConfigError::ParseFile(path.to_string(), e))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env("APP_HOST", &mut self.server.host)?;
+        override_from_env("APP_PORT", &mut self.server.port)?;
+        override_from_env("RUST_LOG", &mut self.server.log_filter)?;
+        override_from_env(
+            "SHUTDOWN_TIMEOUT_SECONDS",
+            &mut self.server.shutdown_timeout_seconds,
+        )?;
+        override_from_env("DATABASE_URL", &mut self.database.url)?;
+        override_from_env("DB_MAX_CONNECTIONS", &mut self.database.max_connections)?;
+        override_from_env("DB_MIN_CONNECTIONS", &mut self.database.min_connections)?;
+        override_list_from_env("CORS_ALLOWED_ORIGINS", &mut self.cors.allowed_origins);
+        override_list_from_env("CORS_ALLOWED_METHODS", &mut self.cors.allowed_methods);
+        override_list_from_env("CORS_ALLOWED_HEADERS", &mut self.cors.allowed_headers);
+        override_from_env(
+            "JWT_ACCESS_TTL_MINUTES",
+            &mut self.auth.access_token_ttl_minutes,
+        )?;
+        override_from_env(
+            "JWT_REFRESH_TTL_DAYS",
+            &mut self.auth.refresh_token_ttl_days,
+        )?;
+        override_from_env("BCRYPT_COST", &mut self.auth.bcrypt_cost)?;
+        override_from_env(
+            "RESERVATION_TTL_MINUTES",
+            &mut self.inventory.reservation_ttl_minutes,
+        )?;
+        override_from_env(
+            "PURGE_DELETED_AFTER_DAYS",
+            &mut self.catalog.purge_deleted_after_days,
+        )?;
+        override_from_env("BRAND_DELETE_POLICY", &mut self.catalog.brand_delete_policy)?;
+        override_from_env(
+            "CATEGORY_DELETE_POLICY",
+            &mut self.catalog.category_delete_policy,
+        )?;
+        override_from_env("STORAGE_BACKEND", &mut self.storage.backend)?;
+        override_from_env("STORAGE_LOCAL_DIR", &mut self.storage.local_dir)?;
+        override_from_env("STORAGE_PUBLIC_URL", &mut self.storage.public_url)?;
+        override_from_env("MAX_IMAGE_BYTES", &mut self.storage.max_image_bytes)?;
+        override_from_env("S3_ENDPOINT", &mut self.storage.s3.endpoint)?;
+        override_from_env("S3_BUCKET", &mut self.storage.s3.bucket)?;
+        override_from_env("S3_REGION", &mut self.storage.s3.region)?;
+        override_from_env("S3_ACCESS_KEY_ID", &mut self.storage.s3.access_key_id)?;
+        override_from_env(
+            "S3_SECRET_ACCESS_KEY",
+            &mut self.storage.s3.secret_access_key,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_database()?;
+        self.validate_app()
+    }
+
+    // Only run() reads the database section to connect, an AppState is handed an open connection;
+    fn validate_database(&self) -> Result<(), ConfigError> {
+        if self.database.url.is_empty() {
+            return Err(invalid("database.url", "DATABASE_URL must be set"));
+        }
+
+        if !backend_enabled(&self.database.url) {
+            return Err(invalid(
+                "database.url",
+                "no enabled database backend feature (postgres, sqlite) matches the url scheme",
+            ));
+        }
+
+        if self.database.max_connections == 0 {
+            return Err(invalid("database.max_connections", "must not be 0"));
+        }
+
+        if self.database.min_connections > self.database.max_connections {
+            return Err(invalid(
+                "database.min_connections",
+                "must not exceed database.max_connections",
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Everything but the database section, what AppState::with_config relies on;
+    pub(crate) fn validate_app(&self) -> Result<(), ConfigError> {
+        if self.server.port == 0 {
+            return Err(invalid("server.port", "must not be 0"));
+        }
+
+        EnvFilter::try_new(&self.server.log_filter)
+            .map_err(|e| invalid("server.log_filter", e.to_string()))?;
+
+        // Upper bounds keep every ttl far from chrono's limits when it is added to now on login, refresh or reserve;
+        if !(1..=525_600).contains(&self.auth.access_token_ttl_minutes) {
+            return Err(invalid(
+                "auth.access_token_ttl_minutes",
+                "must be between 1 and 525600",
+            ));
+        }
+
+        if !(1..=36500).contains(&self.auth.refresh_token_ttl_days) {
+            return Err(invalid(
+                "auth.refresh_token_ttl_days",
+                "must be between 1 and 36500",
+            ));
+        }
+
+        // Same bounds bcrypt::hash enforces, checked here so it fails at startup instead of on register;
+        if !(4..=31).contains(&self.auth.bcrypt_cost) {
+            return Err(invalid("auth.bcrypt_cost", "must be between 4 and 31"));
+        }
+
+        if !(1..=525_600).contains(&self.inventory.reservation_ttl_minutes) {
+            return Err(invalid(
+                "inventory.reservation_ttl_minutes",
+                "must be between 1 and 525600",
+            ));
+        }
+
+        // A hundred years at most, so the retention stays far from chrono's limits when it is subtracted from now;
+        if !(0..=36500).contains(&self.catalog.purge_deleted_after_days) {
+            return Err(invalid(
+                "catalog.purge_deleted_after_days",
+                "must be between 0 and 36500",
+            ));
+        }
+
+        let bounds = &self.catalog.price_facet_bounds;
+        if matches!(bounds.first(), Some(&b) if b < 1) || bounds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(invalid(
+                "catalog.price_facet_bounds",
+                "must be positive and strictly ascending",
+            ));
+        }
+
+        self.validate_storage()?;
+
+        self.cors_layer().map(|_| ())
+    }
+
+    fn validate_storage(&self) -> Result<(), ConfigError> {
+        let storage = &self.storage;
+
+        if storage.max_image_bytes == 0 {
+            return Err(invalid("storage.max_image_bytes", "must not be 0"));
+        }
+
+        if storage.thumbnail_size == 0 {
+            return Err(invalid("storage.thumbnail_size", "must not be 0"));
+        }
+
+        match storage.backend {
+            StorageBackend::Local => {
+                if storage.local_dir.is_empty() {
+                    return Err(invalid("storage.local_dir", "must be set"));
+                }
+            }
+            StorageBackend::S3 => {
+                let s3 = &storage.s3;
+
+                match s3.endpoint.parse::<Uri>() {
+                    Ok(uri) if matches!(uri.scheme_str(), Some("http" | "https")) => {}
+                    _ => {
+                        return Err(invalid(
+                            "storage.s3.endpoint",
+                            "must be an http(s) url, e.g. http://localhost:9000",
+                        ))
+                    }
+                }
+
+                for (key, value) in [
+                    ("storage.s3.bucket", &s3.bucket),
+                    ("storage.s3.region", &s3.region),
+                    ("storage.s3.access_key_id", &s3.access_key_id),
+                    ("storage.s3.secret_access_key", &s3.secret_access_key),
+                ] {
+                    if value.is_empty() {
+                        return Err(invalid(key, "must be set for the s3 storage backend"));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.server.host, self.server.port)
+    }
+
+    pub fn shutdown_timeout(&self) -> StdDuration {
+        StdDuration::from_secs(self.server.shutdown_timeout_seconds)
+    }
+
+    // Every connection to an in-memory SQLite database opens its own empty database, so the pool keeps a single one;
+    pub fn connect_options(&self) -> ConnectOptions {
+        let DatabaseConfig {
+            url,
+            max_connections,
+            min_connections,
+        } = &self.database;
+        let (max_connections, min_connections) = match is_in_memory_sqlite(url) {
+            true => (1, 1),
+            false => (*max_connections, *min_connections),
+        };
+
+        let mut options = ConnectOptions::new(url.clone());
+        options
+            .max_connections(max_connections)
+            .min_connections(min_connections);
+
+        options
+    }
+
+    pub fn access_token_ttl(&self) -> Duration {
+        Duration::minutes(self.auth.access_token_ttl_minutes)
+    }
+
+    pub fn refresh_token_ttl(&self) -> Duration {
+        Duration::days(self.auth.refresh_token_ttl_days)
+    }
+
+    pub fn reservation_ttl(&self) -> Duration {
+        Duration::minutes(self.inventory.reservation_ttl_minutes)
+    }
+
+    pub fn purge_retention(&self) -> Option<Duration> {
+        match self.catalog.purge_deleted_after_days {
+            0 => None,
+            days => Some(Duration::days(days)),
+        }
+    }
+
+    pub fn cors_layer(&self) -> Result<CorsLayer, ConfigError> {
+        let CorsConfig {
+            allowed_origins,
+            allowed_methods,
+            allowed_headers,
+        } = &self.cors;
+
+        let origins = if is_wildcard(allowed_origins) {
+            AllowOrigin::from(Any)
+        } else {
+            AllowOrigin::list(parse_all::<HeaderValue>(
+                "cors.allowed_origins",
+                allowed_origins,
+            )?)
+        };
+
+        let methods = if is_wildcard(allowed_methods) {
+            AllowMethods::from(Any)
+        } else {
+            AllowMethods::list(parse_all::<Method>(
+                "cors.allowed_methods",
+                allowed_methods,
+            )?)
+        };
+
+        let headers = if is_wildcard(allowed_headers) {
+            AllowHeaders::from(Any)
+        } else {
+            AllowHeaders::list(parse_all::<HeaderName>(
+                "cors.allowed_headers",
+                allowed_headers,
+            )?)
+        };
+
+        Ok(CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers([REQUEST_ID_HEADER.clone()]))
+    }
+}
+
+fn invalid(key: &'static str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::InvalidValue(key, reason.into())
+}
+
+fn override_from_env<T: FromStr>(key: &'static str, target: &mut T) -> Result<(), ConfigError> {
+    if let Ok(value) = env::var(key) {
+        *target = value
+            .parse()
+            .map_err(|_| invalid(key, format!("cannot parse {:?}", value)))?;
+    }
+
+    Ok(())
+}
+
+// Lists are passed as comma separated values, e.g. CORS_ALLOWED_ORIGINS=http://a.com,http://b.com;
+fn override_list_from_env(key: &'static str, target: &mut Vec<String>) {
+    if let Ok(value) = env::var(key) {
+        *target = value
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+}
+
+fn backend_enabled(url: &str) -> bool {
+    let scheme = url.split(':').next().unwrap_or_default();
+
+    match scheme {
+        "postgres" | "postgresql" => cfg!(feature = "postgres"),
+        "sqlite" => cfg!(feature = "sqlite"),
+        _ => false,
+    }
+}
+
+// sqlite::memory:, or a url asking for mode=memory, a file that happens to be named memory.db is not;
+fn is_in_memory_sqlite(url: &str) -> bool {
+    let rest = if let Some(rest) = url.strip_prefix("sqlite:") {
+        rest
+    } else {
+        return false;
+    };
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    path == ":memory:" || query.split('&').any(|param| param == "mode=memory")
+}
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|v| v == "*")
+}
+
+fn parse_all<T: FromStr>(key: &'static str, values: &[String]) -> Result<Vec<T>, ConfigError> {
+    values
+        .iter()
+        .map(|v| {
+            v.parse::<T>()
+                .map_err(|_| invalid(key, format!("cannot parse {:?}", v)))
+        })
+        .collect()
+}
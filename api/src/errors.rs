@@ -1,6 +1,14 @@
-use axum::{http::StatusCode, Json};
-use serde_json::{json, Value};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::middlewares::current_request_id;
 
 #[derive(Debug, Error)]
 pub enum AppError {
@@ -18,8 +26,12 @@ pub enum AppError {
     TokioRecvError(#[from] tokio::sync::oneshot::error::RecvError),
     #[error(transparent)]
     AxumTypedHeaderError(#[from] axum::extract::rejection::TypedHeaderRejection),
-    // Auth Error
     #[error(transparent)]
+    StorageError(#[from] crate::storage::StorageError),
+    #[error(transparent)]
+    ImageError(#[from] image::ImageError),
+    // Auth Error
+    #[error("Request validation failed")]
     ValidationError(#[from] validator::ValidationErrors),
     #[error("Username is alread taken")]
     DuplicateUsername,
@@ -27,10 +39,18 @@ pub enum AppError {
     DuplicateEmail,
     #[error("Please check your email or password")]
     WrongCredentials,
+    #[error("Token not provided")]
+    MissingToken,
     #[error("Invalid token!")]
     InvalidToken,
     #[error("User not found")]
     UserNotFound,
+    #[error("You are not allowed to access this resource")]
+    Forbidden,
+    #[error("Invalid or expired refresh token")]
+    InvalidRefreshToken,
+    #[error("Refresh token reuse detected. Please login again")]
+    RefreshTokenReused,
     // Path Error
     #[error("Invalid path. Please check the url path")]
     InvalidPath,
@@ -45,13 +65,21 @@ pub enum AppError {
     MissingBodyContentType(String),
     #[error("{0}")]
     BodyBytesRejection(String),
+    #[error("{0}")]
+    InvalidMultipart(String),
     // Query Error
     #[error("{0}")]
     InvalidQuery(String),
     #[error("Page cannot be 0 or lower")]
     InvalidPage,
-    #[error("Size cannot be 0 or lower")]
+    #[error("Size must be between 1 and 100")]
     InvalidSize,
+    #[error("Invalid cursor, start again from the first page")]
+    InvalidCursor,
+    #[error("The search must contain at least one letter or digit")]
+    InvalidSearch,
+    #[error("Prices must not be negative and min_price must not exceed max_price")]
+    InvalidPriceRange,
     // Category Error
     #[error("Category already created")]
     DuplicateCategory,
@@ -61,6 +89,14 @@ pub enum AppError {
     CategoryAlreadyDeleted,
     #[error("Category cannot be restored")]
     CannotRestoreCategory,
+    #[error("Category still has active products")]
+    CategoryInUse,
+    #[error("Category still has active subcategories")]
+    CategoryHasSubcategories,
+    #[error("A category cannot be moved under itself or one of its subcategories")]
+    InvalidCategoryParent,
+    #[error("Parent category is deleted, restore it first")]
+    CategoryParentDeleted,
     // Brand Error
     #[error("Brand already created")]
     DuplicateBrand,
@@ -70,6 +106,8 @@ pub enum AppError {
     BrandAlreadyDeleted,
     #[error("Brand cannot be restored")]
     CannotRestoreBrand,
+    #[error("Brand still has active products")]
+    BrandInUse,
     // Product Error
     #[error("Product already created")]
     ProductAlreadyCreated,
@@ -79,35 +117,104 @@ pub enum AppError {
     CannotRestoreProduct,
     #[error("Product not found")]
     ProductNotFound,
+    #[error("Variant not found")]
+    VariantNotFound,
+    #[error("Product has variants, a variant must be chosen")]
+    VariantRequired,
+    #[error("SKU already used by another variant")]
+    DuplicateSku,
     #[error("Invalid stock amount")]
     InvalidStock,
     #[error("Invalid price")]
     InvalidPrice,
+    #[error("Unsupported currency")]
+    InvalidCurrency,
+    #[error("Changing the currency requires a new price")]
+    CurrencyChangeWithoutPrice,
+    #[error("Stock can only be adjusted for restock, return or adjustment")]
+    InvalidInventoryReason,
+    // Image Error
+    #[error("An image must be uploaded in the `file` field")]
+    ImageRequired,
+    #[error("Only JPEG, PNG and WebP images are accepted")]
+    UnsupportedImageType,
+    #[error("Image is larger than {0} bytes")]
+    ImageTooLarge(usize),
+    #[error("File is not a valid image of its content type")]
+    InvalidImage,
+    #[error("Image not found")]
+    ImageNotFound,
+    #[error("The order must list every image of the product exactly once")]
+    InvalidImageOrder,
     // Cart Error
     #[error("Invalid quantity")]
     InvalidQuantity,
     #[error("Not sufficient currently available item stock")]
     InsufficientStock,
+    // Order Error
+    #[error("Cart is empty")]
+    EmptyCart,
+    #[error("Order not found")]
+    OrderNotFound,
+    #[error("Cannot change order status from {0} to {1}")]
+    InvalidOrderTransition(String, String),
+    #[error("Order is already closed")]
+    OrderAlreadyClosed,
+    #[error("Cannot combine amounts in different currencies")]
+    CurrencyMismatch,
+    #[error("Amount is too large")]
+    AmountOverflow,
 }
 
 // 1. APIResult is an Option enum for handling all internal API process that could potentially fail;
 // 2. Anything that returns an APIResult can be propagated by using ? in the end of expression at the caller code;
-// 3. We must transform APIResult Error variant into an APIError type should the caller code had an APIResponse return type (Using the From trait defined below);
+// 3. Handlers return an APIResponse, AppError implements IntoResponse so the same ? works there and the error is rendered as an ErrorResponse;
 // 4. To transform it, if its an external crate error we need to use the .map_err fn then mapped that crate error from the closure argument into Error::ErrorKind(e: ExternalCrateErrorType) or just return Err(Error::SomeError) if it was this crate error;
 pub type APIResult<T> = std::result::Result<T, AppError>;
-pub type APIError = (StatusCode, Json<Value>);
-pub type APIResponse<T> = std::result::Result<T, APIError>;
+pub type APIResponse<T> = std::result::Result<T, AppError>;
+
+// The body of every error response, kept as a struct so the OpenAPI document describes the same envelope;
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    success: bool,
+    // Stable identifier clients can match on, the message is meant for humans and may change;
+    #[schema(example = "PRODUCT_NOT_FOUND")]
+    code: &'static str,
+    #[schema(example = 400)]
+    status: u16,
+    message: String,
+    // Only present for validation failures, every invalid field with its messages;
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>, example = json!({"name": ["Name is required"]}))]
+    errors: Option<BTreeMap<String, Vec<String>>>,
+    // Same value as the x-request-id response header;
+    request_id: Option<String>,
+}
 
-impl From<AppError> for APIError {
-    fn from(err: AppError) -> Self {
-        let status_code = match err {
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        // No wildcard arm on purpose, a new variant must be given its status here;
+        match self {
+            // Server errors;
+            AppError::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::DBError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::BcryptError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::JWTError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::TokioRecvError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::AxumTypedHeaderError(_) => StatusCode::BAD_REQUEST,
+            AppError::StorageError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::ImageError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             // Auth errors;
             AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
             AppError::DuplicateUsername => StatusCode::CONFLICT,
             AppError::DuplicateEmail => StatusCode::CONFLICT,
             AppError::WrongCredentials => StatusCode::UNAUTHORIZED,
+            AppError::MissingToken => StatusCode::UNAUTHORIZED,
             AppError::InvalidToken => StatusCode::UNAUTHORIZED,
-            AppError::UserNotFound => StatusCode::BAD_REQUEST,
+            AppError::UserNotFound => StatusCode::NOT_FOUND,
+            AppError::Forbidden => StatusCode::FORBIDDEN,
+            AppError::InvalidRefreshToken => StatusCode::UNAUTHORIZED,
+            AppError::RefreshTokenReused => StatusCode::UNAUTHORIZED,
             // Path error;
             AppError::InvalidPath => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::PathRequired(_) => StatusCode::BAD_REQUEST,
@@ -115,35 +222,210 @@ impl From<AppError> for APIError {
             AppError::InvalidBodyType(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::InvalidBodySyntax(_) => StatusCode::BAD_REQUEST,
             AppError::MissingBodyContentType(_) => StatusCode::BAD_REQUEST,
+            AppError::BodyBytesRejection(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidMultipart(_) => StatusCode::BAD_REQUEST,
             // Query error;
             AppError::InvalidQuery(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::InvalidPage => StatusCode::BAD_REQUEST,
             AppError::InvalidSize => StatusCode::BAD_REQUEST,
+            AppError::InvalidCursor => StatusCode::BAD_REQUEST,
+            AppError::InvalidSearch => StatusCode::BAD_REQUEST,
+            AppError::InvalidPriceRange => StatusCode::BAD_REQUEST,
             // Category errors;
             AppError::DuplicateCategory => StatusCode::CONFLICT,
-            AppError::CategoryNotFound => StatusCode::BAD_REQUEST,
+            AppError::CategoryNotFound => StatusCode::NOT_FOUND,
             AppError::CategoryAlreadyDeleted => StatusCode::CONFLICT,
             AppError::CannotRestoreCategory => StatusCode::BAD_REQUEST,
+            AppError::CategoryInUse => StatusCode::CONFLICT,
+            AppError::CategoryHasSubcategories => StatusCode::CONFLICT,
+            AppError::InvalidCategoryParent => StatusCode::BAD_REQUEST,
+            AppError::CategoryParentDeleted => StatusCode::CONFLICT,
             // Brand errors;
             AppError::DuplicateBrand => StatusCode::CONFLICT,
-            AppError::BrandNotFound => StatusCode::BAD_REQUEST,
+            AppError::BrandNotFound => StatusCode::NOT_FOUND,
             AppError::BrandAlreadyDeleted => StatusCode::CONFLICT,
+            AppError::CannotRestoreBrand => StatusCode::BAD_REQUEST,
+            AppError::BrandInUse => StatusCode::CONFLICT,
             // Product errors;
             AppError::ProductAlreadyCreated => StatusCode::CONFLICT,
             AppError::ProductAlreadyDeleted => StatusCode::CONFLICT,
-            AppError::ProductNotFound => StatusCode::BAD_REQUEST,
+            AppError::ProductNotFound => StatusCode::NOT_FOUND,
+            AppError::VariantNotFound => StatusCode::NOT_FOUND,
+            AppError::VariantRequired => StatusCode::BAD_REQUEST,
+            AppError::DuplicateSku => StatusCode::CONFLICT,
             AppError::CannotRestoreProduct => StatusCode::BAD_REQUEST,
             AppError::InvalidStock => StatusCode::BAD_REQUEST,
             AppError::InvalidPrice => StatusCode::BAD_REQUEST,
+            AppError::InvalidCurrency => StatusCode::BAD_REQUEST,
+            AppError::CurrencyChangeWithoutPrice => StatusCode::BAD_REQUEST,
+            AppError::InvalidInventoryReason => StatusCode::BAD_REQUEST,
+            // Image errors;
+            AppError::ImageRequired => StatusCode::BAD_REQUEST,
+            AppError::UnsupportedImageType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::ImageTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::InvalidImage => StatusCode::BAD_REQUEST,
+            AppError::ImageNotFound => StatusCode::NOT_FOUND,
+            AppError::InvalidImageOrder => StatusCode::BAD_REQUEST,
             // Cart errors;
             AppError::InvalidQuantity => StatusCode::BAD_REQUEST,
             AppError::InsufficientStock => StatusCode::BAD_REQUEST,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+            // Order errors;
+            AppError::EmptyCart => StatusCode::BAD_REQUEST,
+            AppError::OrderNotFound => StatusCode::NOT_FOUND,
+            AppError::InvalidOrderTransition(_, _) => StatusCode::CONFLICT,
+            AppError::OrderAlreadyClosed => StatusCode::CONFLICT,
+            AppError::CurrencyMismatch => StatusCode::BAD_REQUEST,
+            AppError::AmountOverflow => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    // Codes are part of the API contract, never rename one once it has been released;
+    pub fn code(&self) -> &'static str {
+        match self {
+            // Server errors;
+            AppError::ServerError => "INTERNAL_ERROR",
+            AppError::DBError(_) => "DATABASE_ERROR",
+            AppError::BcryptError(_) => "INTERNAL_ERROR",
+            AppError::JWTError(_) => "TOKEN_ERROR",
+            AppError::TokioRecvError(_) => "INTERNAL_ERROR",
+            AppError::AxumTypedHeaderError(_) => "INVALID_HEADER",
+            AppError::StorageError(_) => "STORAGE_ERROR",
+            AppError::ImageError(_) => "INTERNAL_ERROR",
+            // Auth errors;
+            AppError::ValidationError(_) => "VALIDATION_FAILED",
+            AppError::DuplicateUsername => "DUPLICATE_USERNAME",
+            AppError::DuplicateEmail => "DUPLICATE_EMAIL",
+            AppError::WrongCredentials => "WRONG_CREDENTIALS",
+            AppError::MissingToken => "MISSING_TOKEN",
+            AppError::InvalidToken => "INVALID_TOKEN",
+            AppError::UserNotFound => "USER_NOT_FOUND",
+            AppError::Forbidden => "FORBIDDEN",
+            AppError::InvalidRefreshToken => "INVALID_REFRESH_TOKEN",
+            AppError::RefreshTokenReused => "REFRESH_TOKEN_REUSED",
+            // Path error;
+            AppError::InvalidPath => "INVALID_PATH",
+            AppError::PathRequired(_) => "PATH_REQUIRED",
+            // Body error;
+            AppError::InvalidBodyType(_) => "INVALID_BODY_TYPE",
+            AppError::InvalidBodySyntax(_) => "INVALID_BODY_SYNTAX",
+            AppError::MissingBodyContentType(_) => "MISSING_CONTENT_TYPE",
+            AppError::BodyBytesRejection(_) => "INVALID_BODY",
+            AppError::InvalidMultipart(_) => "INVALID_MULTIPART",
+            // Query error;
+            AppError::InvalidQuery(_) => "INVALID_QUERY",
+            AppError::InvalidPage => "INVALID_PAGE",
+            AppError::InvalidSize => "INVALID_SIZE",
+            AppError::InvalidCursor => "INVALID_CURSOR",
+            AppError::InvalidSearch => "INVALID_SEARCH",
+            AppError::InvalidPriceRange => "INVALID_PRICE_RANGE",
+            // Category errors;
+            AppError::DuplicateCategory => "DUPLICATE_CATEGORY",
+            AppError::CategoryNotFound => "CATEGORY_NOT_FOUND",
+            AppError::CategoryAlreadyDeleted => "CATEGORY_ALREADY_DELETED",
+            AppError::CannotRestoreCategory => "CATEGORY_NOT_RESTORABLE",
+            AppError::CategoryInUse => "CATEGORY_IN_USE",
+            AppError::CategoryHasSubcategories => "CATEGORY_HAS_SUBCATEGORIES",
+            AppError::InvalidCategoryParent => "INVALID_CATEGORY_PARENT",
+            AppError::CategoryParentDeleted => "CATEGORY_PARENT_DELETED",
+            // Brand errors;
+            AppError::DuplicateBrand => "DUPLICATE_BRAND",
+            AppError::BrandNotFound => "BRAND_NOT_FOUND",
+            AppError::BrandAlreadyDeleted => "BRAND_ALREADY_DELETED",
+            AppError::CannotRestoreBrand => "BRAND_NOT_RESTORABLE",
+            AppError::BrandInUse => "BRAND_IN_USE",
+            // Product errors;
+            AppError::ProductAlreadyCreated => "DUPLICATE_PRODUCT",
+            AppError::ProductAlreadyDeleted => "PRODUCT_ALREADY_DELETED",
+            AppError::CannotRestoreProduct => "PRODUCT_NOT_RESTORABLE",
+            AppError::ProductNotFound => "PRODUCT_NOT_FOUND",
+            AppError::VariantNotFound => "VARIANT_NOT_FOUND",
+            AppError::VariantRequired => "VARIANT_REQUIRED",
+            AppError::DuplicateSku => "DUPLICATE_SKU",
+            AppError::InvalidStock => "INVALID_STOCK",
+            AppError::InvalidPrice => "INVALID_PRICE",
+            AppError::InvalidCurrency => "INVALID_CURRENCY",
+            AppError::CurrencyChangeWithoutPrice => "CURRENCY_CHANGE_WITHOUT_PRICE",
+            AppError::InvalidInventoryReason => "INVALID_INVENTORY_REASON",
+            // Image errors;
+            AppError::ImageRequired => "IMAGE_REQUIRED",
+            AppError::UnsupportedImageType => "UNSUPPORTED_IMAGE_TYPE",
+            AppError::ImageTooLarge(_) => "IMAGE_TOO_LARGE",
+            AppError::InvalidImage => "INVALID_IMAGE",
+            AppError::ImageNotFound => "IMAGE_NOT_FOUND",
+            AppError::InvalidImageOrder => "INVALID_IMAGE_ORDER",
+            // Cart errors;
+            AppError::InvalidQuantity => "INVALID_QUANTITY",
+            AppError::InsufficientStock => "INSUFFICIENT_STOCK",
+            // Order errors;
+            AppError::EmptyCart => "EMPTY_CART",
+            AppError::OrderNotFound => "ORDER_NOT_FOUND",
+            AppError::InvalidOrderTransition(_, _) => "INVALID_ORDER_TRANSITION",
+            AppError::OrderAlreadyClosed => "ORDER_ALREADY_CLOSED",
+            AppError::CurrencyMismatch => "CURRENCY_MISMATCH",
+            AppError::AmountOverflow => "AMOUNT_OVERFLOW",
+        }
+    }
+
+    // validator keeps nested struct/list errors apart, our payloads are flat so the field errors are enough;
+    fn field_errors(&self) -> Option<BTreeMap<String, Vec<String>>> {
+        let errors = if let AppError::ValidationError(errors) = self {
+            errors
+        } else {
+            return None;
+        };
+
+        let fields = errors
+            .field_errors()
+            .into_iter()
+            .map(|(field, errors)| {
+                let messages = errors
+                    .iter()
+                    .map(|e| match &e.message {
+                        Some(message) => message.to_string(),
+                        None => e.code.to_string(),
+                    })
+                    .collect();
+
+                (field.to_string(), messages)
+            })
+            .collect();
+
+        Some(fields)
+    }
+
+    fn to_error_response(&self) -> ErrorResponse {
+        let status = self.status_code();
+        let request_id = current_request_id();
+
+        // Server side failures (SQL, hashing, token encoding...) may describe internals,
+        // the detail goes to the log and the client only gets the request id to report;
+        let message = if status.is_server_error() {
+            tracing::error!(
+                request_id = request_id.as_deref().unwrap_or("-"),
+                code = self.code(),
+                error = %self,
+                "Request failed with an internal error"
+            );
+
+            AppError::ServerError.to_string()
+        } else {
+            // err.to_string() will consumed the message defined in #[error(err_message_here)] macro;
+            self.to_string()
         };
 
-        // err.to_string() will consumed the message defined in #[error(err_message_here)] macro;
-        let payload = json!({ "success": false, "message": err.to_string() });
+        ErrorResponse {
+            success: false,
+            code: self.code(),
+            status: status.as_u16(),
+            message,
+            errors: self.field_errors(),
+            request_id,
+        }
+    }
+}
 
-        (status_code, Json(payload))
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        (self.status_code(), Json(self.to_error_response())).into_response()
     }
 }
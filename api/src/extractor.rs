@@ -1,44 +1,193 @@
 use axum::{
+    async_trait,
     extract::{
+        multipart::{MultipartError, MultipartRejection},
         rejection::{JsonRejection, PathRejection, QueryRejection},
-        Path, Query,
+        FromRequest, FromRequestParts, Multipart, Path, Query,
     },
+    http::{request::Parts, Request},
     Json,
 };
+use axum_extra::extract::{Query as ListQuery, QueryRejection as ListQueryRejection};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use validator::Validate;
 
-use crate::errors::{APIResult, AppError};
+use crate::errors::AppError;
 
-pub type ReqBody<T> = Result<Json<T>, JsonRejection>;
-pub fn body_extractor<T>(body: ReqBody<T>) -> APIResult<T> {
-    match body {
-        Ok(b) => Ok(b.0),
-        Err(JsonRejection::JsonDataError(e)) => Err(AppError::InvalidBodyType(e.to_string())),
-        Err(JsonRejection::JsonSyntaxError(e)) => Err(AppError::InvalidBodySyntax(e.to_string())),
-        Err(JsonRejection::MissingJsonContentType(e)) => {
-            Err(AppError::MissingBodyContentType(e.to_string()))
+// Drop-in replacements for axum's Json, Query, Path and Multipart whose rejections go through AppError,
+// so a malformed request gets the same error envelope as any other failure;
+
+pub struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<S, B, T> FromRequest<S, B> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, B, Rejection = JsonRejection>,
+    S: Send + Sync,
+    B: Send + 'static,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(body) = Json::<T>::from_request(req, state).await?;
+
+        Ok(Self(body))
+    }
+}
+
+// Same as ApiJson but the payload must also pass its validator rules before reaching the handler;
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<S, B, T> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: Validate,
+    Json<T>: FromRequest<S, B, Rejection = JsonRejection>,
+    S: Send + Sync,
+    B: Send + 'static,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let ApiJson(body) = ApiJson::<T>::from_request(req, state).await?;
+        body.validate()?;
+
+        Ok(Self(body))
+    }
+}
+
+pub struct ApiQuery<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<T>::from_request_parts(parts, state).await?;
+
+        Ok(Self(query))
+    }
+}
+
+// For query parameters that repeat (brand_id[]=1&brand_id[]=2), which ApiQuery cannot read into a Vec;
+// It is axum-extra's Query, where an empty value counts as missing, so it is only meant for the lists,
+// everything else of the query string keeps going through ApiQuery;
+pub struct ApiQueryList<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ApiQueryList<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ListQuery(query) = ListQuery::<T>::from_request_parts(parts, state).await?;
+
+        Ok(Self(query))
+    }
+}
+
+pub struct ApiPath<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ApiPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(path) = Path::<T>::from_request_parts(parts, state).await?;
+
+        Ok(Self(path))
+    }
+}
+
+pub struct ApiMultipart(pub Multipart);
+
+#[async_trait]
+impl<S, B> FromRequest<S, B> for ApiMultipart
+where
+    Multipart: FromRequest<S, B, Rejection = MultipartRejection>,
+    S: Send + Sync,
+    B: Send + 'static,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let multipart = Multipart::from_request(req, state).await?;
+
+        Ok(Self(multipart))
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            // The source carries serde's reason, e.g. the unknown field;
+            JsonRejection::JsonDataError(e) => AppError::InvalidBodyType(match e.source() {
+                Some(source) => format!("{}: {}", e, source),
+                None => e.to_string(),
+            }),
+            JsonRejection::JsonSyntaxError(e) => AppError::InvalidBodySyntax(e.to_string()),
+            JsonRejection::MissingJsonContentType(e) => {
+                AppError::MissingBodyContentType(e.to_string())
+            }
+            JsonRejection::BytesRejection(e) => AppError::BodyBytesRejection(e.to_string()),
+            _ => AppError::ServerError,
+        }
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        match rejection {
+            QueryRejection::FailedToDeserializeQueryString(e) => {
+                AppError::InvalidQuery(e.to_string())
+            }
+            _ => AppError::ServerError,
+        }
+    }
+}
+
+impl From<ListQueryRejection> for AppError {
+    fn from(rejection: ListQueryRejection) -> Self {
+        match rejection {
+            ListQueryRejection::FailedToDeserializeQueryString(e) => {
+                AppError::InvalidQuery(format!("Failed to deserialize query string: {}", e))
+            }
+            _ => AppError::ServerError,
         }
-        Err(JsonRejection::BytesRejection(e)) => Err(AppError::BodyBytesRejection(e.to_string())),
-        _ => Err(AppError::ServerError),
     }
 }
 
-pub type ReqQuery<T> = Result<Query<T>, QueryRejection>;
-pub fn query_extractor<T>(query: ReqQuery<T>) -> APIResult<T> {
-    match query {
-        Ok(q) => Ok(q.0),
-        Err(QueryRejection::FailedToDeserializeQueryString(e)) => {
-            Err(AppError::InvalidQuery(e.to_string()))
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        match rejection {
+            PathRejection::FailedToDeserializePathParams(_) => AppError::InvalidPath,
+            PathRejection::MissingPathParams(e) => AppError::PathRequired(e.to_string()),
+            _ => AppError::ServerError,
         }
-        _ => Err(AppError::ServerError),
     }
 }
 
-pub type ReqPath<T> = Result<Path<T>, PathRejection>;
-pub fn path_extractor<T>(path: ReqPath<T>) -> APIResult<T> {
-    match path {
-        Ok(p) => Ok(p.0),
-        Err(PathRejection::FailedToDeserializePathParams(_)) => Err(AppError::InvalidPath),
-        Err(PathRejection::MissingPathParams(e)) => Err(AppError::PathRequired(e.to_string())),
-        _ => Err(AppError::ServerError),
+impl From<MultipartRejection> for AppError {
+    fn from(rejection: MultipartRejection) -> Self {
+        AppError::InvalidMultipart(rejection.to_string())
+    }
+}
+
+// Malformed parts surface while the fields are read, after the extractor already succeeded;
+impl From<MultipartError> for AppError {
+    fn from(err: MultipartError) -> Self {
+        AppError::InvalidMultipart(err.to_string())
     }
 }
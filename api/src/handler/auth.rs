@@ -8,10 +8,13 @@ use axum::{
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use validator::Validate;
 
-use crate::errors::APIResponse;
-use crate::handler::validate_payload;
+use ::entity::sea_orm_active_enums::UserRole;
+
+use crate::errors::{APIResponse, AppError};
+use crate::extractor::{ApiJson, ValidatedJson};
 use crate::services::AuthService;
 use crate::utils::{
     encryption::hash_password,
@@ -27,7 +30,7 @@ lazy_static! {
     .unwrap();
 }
 
-#[derive(Deserialize, Debug, Validate)]
+#[derive(Deserialize, Debug, Validate, ToSchema)]
 pub struct RegisterRequest {
     pub username: String,
     #[validate(email)]
@@ -39,25 +42,34 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct RegisterResponse {
     success: bool,
     message: &'static str,
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, body = RegisterResponse),
+        (status = 400, description = "Invalid payload", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse),
+    ),
+)]
 pub async fn register_user(
     State(state): State<AppState>,
-    Json(body): Json<RegisterRequest>,
+    ValidatedJson(body): ValidatedJson<RegisterRequest>,
 ) -> APIResponse<(StatusCode, Json<RegisterResponse>)> {
-    validate_payload(&body)?;
-
     let db = &state.conn;
     let RegisterRequest {
         username,
         email,
         password,
     } = body;
-    let password = hash_password(password).await?;
+    let password = hash_password(password, state.config.auth.bcrypt_cost).await?;
 
     AuthService::register_user(db, username, email, password).await?;
 
@@ -70,76 +82,184 @@ pub async fn register_user(
     ))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct LoginRequest {
     email: String,
     password: String,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct LoginResponse {
     success: bool,
     token: String,
+    refresh_token: String,
+    expires_in: i64,
     message: &'static str,
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        (status = 401, description = "Wrong email or password", body = ErrorResponse),
+    ),
+)]
 pub async fn login(
     State(state): State<AppState>,
-    Json(body): Json<LoginRequest>,
+    ApiJson(body): ApiJson<LoginRequest>,
 ) -> APIResponse<(StatusCode, Json<LoginResponse>)> {
     let db = &state.conn;
     let LoginRequest { email, password } = body;
 
     let user = AuthService::login_user(db, email, password).await?;
-    let token = generate_token(user.id)?;
+    let token = generate_token(user.id, user.role, state.config.access_token_ttl())?;
+    let refresh_token =
+        AuthService::issue_refresh_token(db, user.id, None, state.config.refresh_token_ttl())
+            .await?;
 
     Ok((
         StatusCode::OK,
         Json(LoginResponse {
             success: true,
             token,
+            refresh_token,
+            expires_in: state.config.access_token_ttl().num_seconds(),
             message: "Login success!",
         }),
     ))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct RefreshTokenRequest {
+    refresh_token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        (status = 401, description = "Invalid, expired or reused refresh token", body = ErrorResponse),
+    ),
+)]
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    ApiJson(body): ApiJson<RefreshTokenRequest>,
+) -> APIResponse<(StatusCode, Json<LoginResponse>)> {
+    let RefreshTokenRequest { refresh_token } = body;
+    let db = &state.conn;
+
+    let (user, refresh_token) =
+        AuthService::rotate_refresh_token(db, refresh_token, state.config.refresh_token_ttl())
+            .await?;
+    let token = generate_token(user.id, user.role, state.config.access_token_ttl())?;
+
+    Ok((
+        StatusCode::OK,
+        Json(LoginResponse {
+            success: true,
+            token,
+            refresh_token,
+            expires_in: state.config.access_token_ttl().num_seconds(),
+            message: "Token refreshed!",
+        }),
+    ))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LogoutResponse {
+    success: bool,
+    message: &'static str,
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, body = LogoutResponse),
+        (status = 401, description = "Invalid refresh token", body = ErrorResponse),
+    ),
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    ApiJson(body): ApiJson<RefreshTokenRequest>,
+) -> APIResponse<(StatusCode, Json<LogoutResponse>)> {
+    let RefreshTokenRequest { refresh_token } = body;
+    let db = &state.conn;
+
+    AuthService::revoke_refresh_token(db, refresh_token).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(LogoutResponse {
+            success: true,
+            message: "Logout success!",
+        }),
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserData {
     id: i32,
     username: String,
     email: String,
+    role: UserRole,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PersistentLoginResponse {
     success: bool,
-    token: String,
     data: UserData,
 }
 
+#[utoipa::path(
+    get,
+    path = "/auth/persistent",
+    tag = "auth",
+    responses(
+        (status = 200, body = PersistentLoginResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 404, description = "User no longer exists", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn persistent_login(
     State(state): State<AppState>,
-    TypedHeader(user_token): TypedHeader<Authorization<Bearer>>,
+    user_token: Option<TypedHeader<Authorization<Bearer>>>,
 ) -> APIResponse<(StatusCode, Json<PersistentLoginResponse>)> {
     let db = &state.conn;
 
+    // Same as user_auth_required, a missing or malformed header gets the error envelope;
+    let user_token = if let Some(TypedHeader(t)) = user_token {
+        t
+    } else {
+        return Err(AppError::MissingToken);
+    };
+
     let verified_token = verify_token(user_token.token())?;
     let user_id = verified_token.user_id;
 
+    // The access token is not re-minted here anymore, renewal goes through the refresh token rotation;
     let user = AuthService::persistent_login(db, user_id).await?;
-    let token = generate_token(user.id)?;
 
     let data = UserData {
         id: user.id,
         username: user.username,
         email: user.email,
+        role: user.role,
     };
 
     Ok((
         StatusCode::OK,
         Json(PersistentLoginResponse {
             success: true,
-            token,
             data,
         }),
     ))
@@ -1,27 +1,39 @@
-use axum::extract::rejection::{PathRejection, QueryRejection};
-use axum::extract::{Path, Query};
 use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use ::entity::brand;
 
-use crate::extractor::{path_extractor, query_extractor};
-use crate::services::BrandService;
+use crate::extractor::{ApiJson, ApiPath, ApiQuery};
+use crate::services::{BrandService, PageRequest};
 use crate::{errors::APIResponse, AppState};
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct BrandResponse {
     success: bool,
     message: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct CreateBrandRequest {
     name: String,
 }
+#[utoipa::path(
+    post,
+    path = "/brands/create",
+    tag = "brands",
+    request_body = CreateBrandRequest,
+    responses(
+        (status = 201, body = BrandResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 409, description = "Brand already created", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn create_brand(
     State(state): State<AppState>,
-    Json(body): Json<CreateBrandRequest>,
+    ApiJson(body): ApiJson<CreateBrandRequest>,
 ) -> APIResponse<(StatusCode, Json<BrandResponse>)> {
     let db = &state.conn;
 
@@ -38,55 +50,94 @@ pub async fn create_brand(
     ))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FindBrandsParams {
     keyword: Option<String>,
     page: Option<i32>,
+    // At most 100;
     size: Option<i32>,
+    // next_cursor or prev_cursor of a previous response, page is then ignored;
+    cursor: Option<String>,
+    // false skips counting total_items and total_page;
+    count: Option<bool>,
     all: Option<bool>,
 }
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct FindBrandsResponse {
     success: bool,
-    total_items: u64,
-    total_page: u64,
+    total_items: Option<u64>,
+    total_page: Option<u64>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
+    #[schema(value_type = Vec<Brand>)]
     data: Vec<brand::Model>,
 }
+#[utoipa::path(
+    get,
+    path = "/brands/find",
+    tag = "brands",
+    params(
+        FindBrandsParams,
+    ),
+    responses(
+        (status = 200, body = FindBrandsResponse),
+        (status = 400, description = "Invalid page, size or cursor", body = ErrorResponse),
+    ),
+)]
 pub async fn find_brands(
     State(state): State<AppState>,
-    params: Result<Query<FindBrandsParams>, QueryRejection>,
+    ApiQuery(params): ApiQuery<FindBrandsParams>,
 ) -> APIResponse<(StatusCode, Json<FindBrandsResponse>)> {
-    let params = query_extractor(params)?;
-
     let FindBrandsParams {
         keyword,
         page,
         size,
+        cursor,
+        count,
         all,
     } = params;
 
     let db = &state.conn;
-    let (data, total_items, total_page) = BrandService::get(db, keyword, page, size, all).await?;
+    let request = PageRequest::new(page, size, cursor, count)?;
+    let page = BrandService::get(db, keyword, all, request).await?;
 
     Ok((
         StatusCode::OK,
         Json(FindBrandsResponse {
             success: true,
-            total_items,
-            total_page,
-            data,
+            total_items: page.total_items,
+            total_page: page.total_page,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            data: page.data,
         }),
     ))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/brands/delete/{id}",
+    tag = "brands",
+    params(
+        ("id" = i32, Path, description = "Brand id"),
+    ),
+    responses(
+        (status = 200, body = BrandResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Brand not found", body = ErrorResponse),
+        (status = 409, description = "Brand already deleted, or still has active products under the restrict policy", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn delete_brand(
     State(state): State<AppState>,
-    id: Result<Path<i32>, PathRejection>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<BrandResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
-    BrandService::delete(db, id).await?;
+    BrandService::delete(db, id, state.config.catalog.brand_delete_policy).await?;
 
     Ok((
         StatusCode::OK,
@@ -97,16 +148,31 @@ pub async fn delete_brand(
     ))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RestoreBrandResponse {
     success: bool,
     message: &'static str,
 }
+#[utoipa::path(
+    patch,
+    path = "/brands/restore/{id}",
+    tag = "brands",
+    params(
+        ("id" = i32, Path, description = "Brand id"),
+    ),
+    responses(
+        (status = 200, body = RestoreBrandResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Brand not found", body = ErrorResponse),
+        (status = 400, description = "Brand cannot be restored", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn restore_brand(
     State(state): State<AppState>,
-    id: Result<Path<i32>, PathRejection>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<RestoreBrandResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
     BrandService::restore(db, id).await?;
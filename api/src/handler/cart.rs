@@ -1,37 +1,61 @@
 use axum::{extract::State, http::StatusCode, Extension, Json};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     errors::APIResponse,
-    extractor::{body_extractor, query_extractor, ReqBody, ReqQuery},
+    extractor::{ApiJson, ApiQuery},
     middlewares::CurrentUser,
-    services::{CartData, CartService},
+    services::{CartData, CartService, PageRequest},
     AppState,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateOrUpdateCartRequest {
     product_id: i32,
+    // Required for products with variants;
+    variant_id: Option<i32>,
     quantity: i32,
 }
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateOrUpdateCartResponse {
     success: bool,
     message: &'static str,
 }
+#[utoipa::path(
+    post,
+    path = "/carts/create-or-update",
+    tag = "carts",
+    request_body = CreateOrUpdateCartRequest,
+    responses(
+        (status = 201, body = CreateOrUpdateCartResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 400, description = "Invalid quantity, missing variant or insufficient stock", body = ErrorResponse),
+        (status = 404, description = "Product or variant not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn create_or_update_cart(
     State(state): State<AppState>,
     Extension(current_user): Extension<CurrentUser>,
-    body: ReqBody<CreateOrUpdateCartRequest>,
+    ApiJson(body): ApiJson<CreateOrUpdateCartRequest>,
 ) -> APIResponse<(StatusCode, Json<CreateOrUpdateCartResponse>)> {
     let CreateOrUpdateCartRequest {
         product_id,
+        variant_id,
         quantity,
-    } = body_extractor(body)?;
+    } = body;
     let db = &state.conn;
 
-    let update_or_create_cart =
-        CartService::create_or_update(db, current_user.id, product_id, quantity).await?;
+    let update_or_create_cart = CartService::create_or_update(
+        db,
+        current_user.id,
+        product_id,
+        variant_id,
+        quantity,
+        state.config.reservation_ttl(),
+    )
+    .await?;
 
     Ok((
         StatusCode::CREATED,
@@ -42,38 +66,67 @@ pub async fn create_or_update_cart(
     ))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FindCartQuery {
     page: Option<i32>,
+    // At most 100;
     size: Option<i32>,
+    // next_cursor or prev_cursor of a previous response, page is then ignored;
+    cursor: Option<String>,
+    // false skips counting total_items and total_page;
+    count: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FindCartResponse {
     success: bool,
-    total_page: u64,
-    total_items: u64,
+    total_page: Option<u64>,
+    total_items: Option<u64>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
     data: Vec<CartData>,
 }
+#[utoipa::path(
+    get,
+    path = "/carts/find",
+    tag = "carts",
+    params(
+        FindCartQuery,
+    ),
+    responses(
+        (status = 200, body = FindCartResponse),
+        (status = 400, description = "Invalid page, size or cursor", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn find_carts(
     State(state): State<AppState>,
     Extension(current_user): Extension<CurrentUser>,
-    query: ReqQuery<FindCartQuery>,
+    ApiQuery(query): ApiQuery<FindCartQuery>,
 ) -> APIResponse<(StatusCode, Json<FindCartResponse>)> {
-    let query = query_extractor(query)?;
-    let FindCartQuery { page, size } = query;
+    let FindCartQuery {
+        page,
+        size,
+        cursor,
+        count,
+    } = query;
 
     let db = &state.conn;
 
-    let (data, total_items, total_page) = CartService::get(db, current_user.id, page, size).await?;
+    let request = PageRequest::new(page, size, cursor, count)?;
+    let page = CartService::get(db, current_user.id, request).await?;
 
     Ok((
         StatusCode::OK,
         Json(FindCartResponse {
             success: true,
-            total_page,
-            total_items,
-            data,
+            total_page: page.total_page,
+            total_items: page.total_items,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            data: page.data,
         }),
     ))
 }
@@ -1,33 +1,54 @@
-use axum::{
-    extract::{rejection::PathRejection, Path, Query, State},
-    http::StatusCode,
-    Json,
-};
+use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use ::entity::category;
 
-use crate::services::CategoryService;
+use crate::services::{CategoryNode, CategoryService, PageRequest};
 use crate::AppState;
-use crate::{errors::APIResponse, extractor::path_extractor};
+use crate::{
+    errors::APIResponse,
+    extractor::{ApiJson, ApiPath, ApiQuery},
+};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CategoryQuery {
     keyword: Option<String>,
     all: Option<bool>,
     page: Option<i32>,
+    // At most 100;
     size: Option<i32>,
+    // next_cursor or prev_cursor of a previous response, page is then ignored;
+    cursor: Option<String>,
+    // false skips counting total_items and total_page;
+    count: Option<bool>,
 }
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct FindCategoryResponse {
     success: bool,
-    total_items: u64,
-    total_page: u64,
+    total_items: Option<u64>,
+    total_page: Option<u64>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
+    #[schema(value_type = Vec<Category>)]
     data: Vec<category::Model>,
 }
+#[utoipa::path(
+    get,
+    path = "/categories/find",
+    tag = "categories",
+    params(
+        CategoryQuery,
+    ),
+    responses(
+        (status = 200, body = FindCategoryResponse),
+        (status = 400, description = "Invalid page, size or cursor", body = ErrorResponse),
+    ),
+)]
 pub async fn find_category(
     State(state): State<AppState>,
-    Query(query): Query<CategoryQuery>,
+    ApiQuery(query): ApiQuery<CategoryQuery>,
 ) -> APIResponse<(StatusCode, Json<FindCategoryResponse>)> {
     let db = &state.conn;
 
@@ -36,41 +57,61 @@ pub async fn find_category(
         all,
         size,
         page,
+        cursor,
+        count,
     } = query;
 
-    let (data, total_items, total_page) =
-        CategoryService::get(db, keyword, all, page, size).await?;
+    let request = PageRequest::new(page, size, cursor, count)?;
+    let page = CategoryService::get(db, keyword, all, request).await?;
 
     Ok((
         StatusCode::OK,
         Json(FindCategoryResponse {
             success: true,
-            total_items,
-            total_page,
-            data,
+            total_items: page.total_items,
+            total_page: page.total_page,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            data: page.data,
         }),
     ))
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct CategoryResponse {
     success: bool,
     message: &'static str,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct CreateCategoryRequest {
     name: String,
+    // Omitted for a root category;
+    parent_id: Option<i32>,
 }
+#[utoipa::path(
+    post,
+    path = "/categories/create",
+    tag = "categories",
+    request_body = CreateCategoryRequest,
+    responses(
+        (status = 201, body = CategoryResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Parent category not found", body = ErrorResponse),
+        (status = 409, description = "Category already created", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn create_category(
     State(state): State<AppState>,
-    Json(body): Json<CreateCategoryRequest>,
+    ApiJson(body): ApiJson<CreateCategoryRequest>,
 ) -> APIResponse<(StatusCode, Json<CategoryResponse>)> {
     let db = &state.conn;
 
-    let CreateCategoryRequest { name } = body;
+    let CreateCategoryRequest { name, parent_id } = body;
 
-    CategoryService::create(db, name).await?;
+    CategoryService::create(db, name, parent_id).await?;
 
     Ok((
         StatusCode::CREATED,
@@ -81,14 +122,29 @@ pub async fn create_category(
     ))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/categories/delete/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+    ),
+    responses(
+        (status = 200, body = CategoryResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+        (status = 409, description = "Category already deleted, still has active subcategories, or still has active products under the restrict policy", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn delete_category(
     State(state): State<AppState>,
-    id: Result<Path<i32>, PathRejection>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<CategoryResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
-    CategoryService::delete(db, id).await?;
+    CategoryService::delete(db, id, state.config.catalog.category_delete_policy).await?;
 
     Ok((
         StatusCode::OK,
@@ -99,11 +155,27 @@ pub async fn delete_category(
     ))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/categories/restore/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+    ),
+    responses(
+        (status = 200, body = CategoryResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+        (status = 400, description = "Category cannot be restored", body = ErrorResponse),
+        (status = 409, description = "Parent category is deleted", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn restore_category(
     State(state): State<AppState>,
-    id: Result<Path<i32>, PathRejection>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<CategoryResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
     CategoryService::restore(db, id).await?;
@@ -116,3 +188,109 @@ pub async fn restore_category(
         }),
     ))
 }
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CategoryTreeResponse {
+    success: bool,
+    data: Vec<CategoryNode>,
+}
+#[utoipa::path(
+    get,
+    path = "/categories/tree",
+    tag = "categories",
+    responses(
+        (status = 200, body = CategoryTreeResponse),
+    ),
+)]
+pub async fn category_tree(
+    State(state): State<AppState>,
+) -> APIResponse<(StatusCode, Json<CategoryTreeResponse>)> {
+    let db = &state.conn;
+
+    let data = CategoryService::tree(db).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(CategoryTreeResponse {
+            success: true,
+            data,
+        }),
+    ))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct BreadcrumbsResponse {
+    success: bool,
+    #[schema(value_type = Vec<Category>)]
+    data: Vec<category::Model>,
+}
+#[utoipa::path(
+    get,
+    path = "/categories/breadcrumbs/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+    ),
+    responses(
+        (status = 200, description = "The category and its ancestors, root first", body = BreadcrumbsResponse),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+    ),
+)]
+pub async fn category_breadcrumbs(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+) -> APIResponse<(StatusCode, Json<BreadcrumbsResponse>)> {
+    let db = &state.conn;
+
+    let data = CategoryService::breadcrumbs(db, id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(BreadcrumbsResponse {
+            success: true,
+            data,
+        }),
+    ))
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct MoveCategoryRequest {
+    // null moves the category to the root;
+    parent_id: Option<i32>,
+}
+#[utoipa::path(
+    patch,
+    path = "/categories/move/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+    ),
+    request_body = MoveCategoryRequest,
+    responses(
+        (status = 200, body = CategoryResponse),
+        (status = 400, description = "New parent is the category itself or one of its subcategories", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Category or parent not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn move_category(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(body): ApiJson<MoveCategoryRequest>,
+) -> APIResponse<(StatusCode, Json<CategoryResponse>)> {
+    let db = &state.conn;
+
+    let MoveCategoryRequest { parent_id } = body;
+
+    CategoryService::move_to(db, id, parent_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(CategoryResponse {
+            success: true,
+            message: "Category moved successfully!",
+        }),
+    ))
+}
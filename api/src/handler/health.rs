@@ -0,0 +1,76 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::services::{HealthService, MigrationStatus};
+use crate::AppState;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    success: bool,
+    status: &'static str,
+}
+
+// Only tells that the process is up and serving requests, it never touches the database;
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, body = LivenessResponse),
+    ),
+)]
+pub async fn liveness() -> (StatusCode, Json<LivenessResponse>) {
+    (
+        StatusCode::OK,
+        Json(LivenessResponse {
+            success: true,
+            status: "alive",
+        }),
+    )
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    success: bool,
+    status: &'static str,
+    database: &'static str,
+    migrations: Option<MigrationStatus>,
+}
+
+// Ready only when the database answers and every migration has been applied, otherwise 503 so traffic is held back;
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, body = ReadinessResponse),
+        (status = 503, description = "Database down or migrations pending", body = ReadinessResponse),
+    ),
+)]
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let db = &state.conn;
+
+    let database_up = HealthService::ping(db).await.is_ok();
+    let migrations = if database_up {
+        HealthService::migration_status(db).await.ok()
+    } else {
+        None
+    };
+
+    let ready = matches!(&migrations, Some(m) if m.is_up_to_date());
+
+    (
+        if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+        Json(ReadinessResponse {
+            success: ready,
+            status: if ready { "ready" } else { "not_ready" },
+            database: if database_up { "up" } else { "down" },
+            migrations,
+        }),
+    )
+}
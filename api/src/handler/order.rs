@@ -0,0 +1,243 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use ::entity::{
+    order_status_history,
+    sea_orm_active_enums::{OrderStatus, UserRole},
+};
+
+use crate::{
+    errors::{APIResponse, AppError},
+    extractor::{ApiJson, ApiPath, ApiQuery},
+    middlewares::CurrentUser,
+    services::{OrderData, OrderService, OrderSummary},
+    AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckoutResponse {
+    success: bool,
+    message: String,
+    data: OrderSummary,
+}
+#[utoipa::path(
+    post,
+    path = "/orders/checkout",
+    tag = "orders",
+    responses(
+        (status = 201, body = CheckoutResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 400, description = "Cart is empty or stock is insufficient", body = ErrorResponse),
+        (status = 404, description = "A product in the cart no longer exists", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn checkout(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> APIResponse<(StatusCode, Json<CheckoutResponse>)> {
+    let db = &state.conn;
+
+    let order = OrderService::checkout(db, current_user.id).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CheckoutResponse {
+            success: true,
+            message: format!("Created order with id: {}", order.id),
+            data: order,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FindOrdersQuery {
+    page: Option<i32>,
+    size: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FindOrdersResponse {
+    success: bool,
+    total_page: u64,
+    total_items: u64,
+    data: Vec<OrderSummary>,
+}
+#[utoipa::path(
+    get,
+    path = "/orders/find",
+    tag = "orders",
+    params(
+        FindOrdersQuery,
+    ),
+    responses(
+        (status = 200, body = FindOrdersResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn find_orders(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiQuery(query): ApiQuery<FindOrdersQuery>,
+) -> APIResponse<(StatusCode, Json<FindOrdersResponse>)> {
+    let FindOrdersQuery { page, size } = query;
+    let db = &state.conn;
+
+    let (data, total_items, total_page) =
+        OrderService::find(db, current_user.id, page, size).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(FindOrdersResponse {
+            success: true,
+            total_page,
+            total_items,
+            data,
+        }),
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FindOrderResponse {
+    success: bool,
+    data: OrderData,
+}
+#[utoipa::path(
+    get,
+    path = "/orders/{id}",
+    tag = "orders",
+    params(
+        ("id" = i32, Path, description = "Order id"),
+    ),
+    responses(
+        (status = 200, body = FindOrderResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn find_order(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiPath(id): ApiPath<i32>,
+) -> APIResponse<(StatusCode, Json<FindOrderResponse>)> {
+    let db = &state.conn;
+
+    // Admins can look up any order, customers only their own;
+    let owner_id = match current_user.role {
+        UserRole::Admin => None,
+        UserRole::Customer => Some(current_user.id),
+    };
+
+    let data = OrderService::get(db, id, owner_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(FindOrderResponse {
+            success: true,
+            data,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateOrderStatusRequest {
+    status: OrderStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateOrderStatusResponse {
+    success: bool,
+    message: String,
+    data: OrderSummary,
+}
+#[utoipa::path(
+    patch,
+    path = "/orders/{id}/status",
+    tag = "orders",
+    params(
+        ("id" = i32, Path, description = "Order id"),
+    ),
+    request_body = UpdateOrderStatusRequest,
+    responses(
+        (status = 200, body = UpdateOrderStatusResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Customers may only cancel their own orders", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 409, description = "Transition not allowed from the current status", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_order_status(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(body): ApiJson<UpdateOrderStatusRequest>,
+) -> APIResponse<(StatusCode, Json<UpdateOrderStatusResponse>)> {
+    let UpdateOrderStatusRequest { status } = body;
+    let db = &state.conn;
+
+    // Customers may only cancel their own orders, every other transition is up to the admins;
+    let owner_id = match current_user.role {
+        UserRole::Admin => None,
+        UserRole::Customer if status == OrderStatus::Cancelled => Some(current_user.id),
+        UserRole::Customer => return Err(AppError::Forbidden),
+    };
+
+    let data = OrderService::transition(db, id, status, current_user.id, owner_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(UpdateOrderStatusResponse {
+            success: true,
+            message: format!("Order {} updated successfully", data.id),
+            data,
+        }),
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FindOrderHistoryResponse {
+    success: bool,
+    #[schema(value_type = Vec<OrderStatusHistory>)]
+    data: Vec<order_status_history::Model>,
+}
+#[utoipa::path(
+    get,
+    path = "/orders/{id}/history",
+    tag = "orders",
+    params(
+        ("id" = i32, Path, description = "Order id"),
+    ),
+    responses(
+        (status = 200, body = FindOrderHistoryResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn find_order_history(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiPath(id): ApiPath<i32>,
+) -> APIResponse<(StatusCode, Json<FindOrderHistoryResponse>)> {
+    let db = &state.conn;
+
+    let owner_id = match current_user.role {
+        UserRole::Admin => None,
+        UserRole::Customer => Some(current_user.id),
+    };
+
+    let data = OrderService::history(db, id, owner_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(FindOrderHistoryResponse {
+            success: true,
+            data,
+        }),
+    ))
+}
@@ -1,27 +1,34 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, http::StatusCode, Extension, Json};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-use super::validate_payload;
+use ::entity::{inventory_movement, sea_orm_active_enums::InventoryReason};
+
 use crate::{
-    errors::APIResponse,
-    extractor::{body_extractor, path_extractor, query_extractor, ReqBody, ReqPath, ReqQuery},
-    services::{ProductData, ProductService},
+    errors::{APIResponse, AppError},
+    extractor::{ApiJson, ApiMultipart, ApiPath, ApiQuery, ApiQueryList, ValidatedJson},
+    middlewares::CurrentUser,
+    services::{
+        ImageData, ImageService, InventoryService, ProductData, ProductFacets, ProductService,
+    },
     AppState,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ProductResponse {
     success: bool,
     message: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateProductRequest {
     #[validate(required(message = "Name is required"))]
     name: Option<String>,
     #[validate(required(message = "Price is required"))]
-    price: Option<i32>,
+    price: Option<i64>,
+    currency: Option<String>,
     #[validate(required(message = "Stock is required"))]
     stock: Option<i32>,
     #[validate(required(message = "Category_id is required"))]
@@ -30,33 +37,59 @@ pub struct CreateProductRequest {
     brand_id: Option<i32>,
     description: Option<String>,
 }
+#[derive(Debug)]
+pub struct CreateProductData {
+    pub name: String,
+    pub price: i64,
+    pub currency: Option<String>,
+    pub stock: i32,
+    pub category_id: i32,
+    pub brand_id: i32,
+    pub description: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/products/create",
+    tag = "products",
+    request_body = CreateProductRequest,
+    responses(
+        (status = 201, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "Invalid payload, price or currency", body = ErrorResponse),
+        (status = 404, description = "Category or brand not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn create_product(
     State(state): State<AppState>,
-    Json(body): Json<CreateProductRequest>,
+    Extension(current_user): Extension<CurrentUser>,
+    ValidatedJson(body): ValidatedJson<CreateProductRequest>,
 ) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
-    validate_payload(&body)?;
-
     let db = &state.conn;
 
     let CreateProductRequest {
         name,
         price,
+        currency,
         stock,
         category_id,
         brand_id,
         description,
     } = body;
 
-    let created_product = ProductService::create(
-        db,
-        name.unwrap(),
-        price.unwrap(),
-        stock.unwrap(),
-        category_id.unwrap(),
-        brand_id.unwrap(),
+    let create_data = CreateProductData {
+        name: name.unwrap(),
+        price: price.unwrap(),
+        currency,
+        stock: stock.unwrap(),
+        category_id: category_id.unwrap(),
+        brand_id: brand_id.unwrap(),
         description,
-    )
-    .await?;
+    };
+
+    let created_product = ProductService::create(db, create_data, current_user.id).await?;
 
     Ok((
         StatusCode::CREATED,
@@ -67,65 +100,187 @@ pub async fn create_product(
     ))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateVariantData {
+    #[validate(length(min = 1, message = "SKU is required"))]
+    pub sku: String,
+    // e.g. {"size": "M", "color": "red"};
+    pub options: BTreeMap<String, String>,
+    // Omitted to sell at the product's price;
+    pub price: Option<i64>,
+    pub stock: i32,
+}
+
+#[utoipa::path(
+    post,
+    path = "/products/{id}/variants",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    request_body = CreateVariantData,
+    responses(
+        (status = 201, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "Invalid payload, price or stock", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 409, description = "SKU already used", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn create_variant(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiPath(id): ApiPath<i32>,
+    ValidatedJson(body): ValidatedJson<CreateVariantData>,
+) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
+    let db = &state.conn;
+
+    let created_variant = ProductService::create_variant(db, id, body, current_user.id).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ProductResponse {
+            success: true,
+            message: format!("Created variant with id: {}", created_variant.id),
+        }),
+    ))
+}
+
+// Listing order, the product id breaks ties; without one a search is ordered by relevance;
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductSort {
+    PriceAsc,
+    PriceDesc,
+    Newest,
+    Name,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FindProductParams {
-    keyword: Option<String>,
-    page: Option<i32>,
-    size: Option<i32>,
-    all: Option<bool>,
+    pub keyword: Option<String>,
+    // Full-text search over name, description, brand and category, most relevant first;
+    pub search: Option<String>,
+    pub page: Option<i32>,
+    // At most 100;
+    pub size: Option<i32>,
+    // next_cursor or prev_cursor of a previous response, with the same filters and sort; page is then ignored;
+    pub cursor: Option<String>,
+    // false skips counting total_items and total_page;
+    pub count: Option<bool>,
+    pub all: Option<bool>,
+    // Only products priced in this currency; also the currency of the price bounds and buckets, USD by default;
+    pub currency: Option<String>,
+    // Bounds of the product's lowest price, its cheapest variant's if it has some, in minor units, both included;
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    // true for products that can be bought now, false for sold out ones;
+    pub in_stock: Option<bool>,
+    pub sort: Option<ProductSort>,
 }
-#[derive(Debug, Serialize)]
+// brand_id[] and category_id[] are repeated for several values: brand_id[]=1&brand_id[]=2,
+// the plain brand_id and category_id keys are accepted as well;
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductIdFilters {
+    // Products of any of these brands;
+    #[serde(default, rename = "brand_id[]", alias = "brand_id")]
+    pub brand_ids: Vec<i32>,
+    // Products of any of these categories or their subcategories;
+    #[serde(default, rename = "category_id[]", alias = "category_id")]
+    pub category_ids: Vec<i32>,
+}
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FindProductsResponse {
     success: bool,
-    total_page: u64,
-    total_items: u64,
+    total_page: Option<u64>,
+    total_items: Option<u64>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
     data: Vec<ProductData>,
+    facets: ProductFacets,
 }
+#[utoipa::path(
+    get,
+    path = "/products/find",
+    tag = "products",
+    params(
+        FindProductParams,
+        ProductIdFilters,
+    ),
+    responses(
+        (status = 200, body = FindProductsResponse),
+        (status = 400, description = "Invalid page, size, cursor, search or price range", body = ErrorResponse),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+    ),
+)]
 pub async fn find_products(
     State(state): State<AppState>,
-    query: ReqQuery<FindProductParams>,
+    ApiQuery(query): ApiQuery<FindProductParams>,
+    ApiQueryList(ids): ApiQueryList<ProductIdFilters>,
 ) -> APIResponse<(StatusCode, Json<FindProductsResponse>)> {
-    let query = query_extractor(query)?;
-
-    let FindProductParams {
-        keyword,
-        page,
-        size,
-        all,
-    } = query;
-
     let db = &state.conn;
-    let (data, total_items, total_page) =
-        ProductService::find(db, keyword, page, size, all).await?;
+    let (page, facets) = ProductService::find(
+        db,
+        state.storage(),
+        query,
+        ids,
+        &state.config.catalog.price_facet_bounds,
+    )
+    .await?;
 
     Ok((
         StatusCode::OK,
         Json(FindProductsResponse {
             success: true,
-            total_page,
-            total_items,
-            data,
+            total_page: page.total_page,
+            total_items: page.total_items,
+            next_cursor: page.next_cursor,
+            prev_cursor: page.prev_cursor,
+            data: page.data,
+            facets,
         }),
     ))
 }
 
-#[derive(Debug, Deserialize)]
+// Unknown fields are refused, stock in particular only changes through /products/{id}/adjust-stock;
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateProductData {
     pub name: Option<String>,
-    pub price: Option<i32>,
-    pub stock: Option<i32>,
+    pub price: Option<i64>,
+    pub currency: Option<String>,
     pub description: Option<String>,
     pub category_id: Option<i32>,
     pub brand_id: Option<i32>,
 }
 
+#[utoipa::path(
+    patch,
+    path = "/products/update/{id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    request_body = UpdateProductData,
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "Invalid payload, price or currency", body = ErrorResponse),
+        (status = 404, description = "Product, category or brand not found", body = ErrorResponse),
+        (status = 422, description = "Unknown field, e.g. stock", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn update_product(
     State(state): State<AppState>,
-    id: ReqPath<i32>,
-    update_data: ReqBody<UpdateProductData>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(update_data): ApiJson<UpdateProductData>,
 ) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
-    let id = path_extractor(id)?;
-    let update_data = body_extractor(update_data)?;
     let db = &state.conn;
 
     ProductService::update(db, id, update_data).await?;
@@ -139,11 +294,26 @@ pub async fn update_product(
     ))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/products/delete/{id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 409, description = "Product already deleted", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn delete_product(
     State(state): State<AppState>,
-    id: ReqPath<i32>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
     ProductService::delete(db, id).await?;
@@ -157,11 +327,26 @@ pub async fn delete_product(
     ))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/products/restore/{id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 400, description = "Product cannot be restored", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn restore_product(
     State(state): State<AppState>,
-    id: ReqPath<i32>,
+    ApiPath(id): ApiPath<i32>,
 ) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
-    let id = path_extractor(id)?;
     let db = &state.conn;
 
     ProductService::restore(db, id).await?;
@@ -174,3 +359,348 @@ pub async fn restore_product(
         }),
     ))
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdjustStockRequest {
+    // Required for products with variants, the variant's stock is adjusted;
+    variant_id: Option<i32>,
+    quantity: i32,
+    reason: InventoryReason,
+    note: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StockData {
+    product_id: i32,
+    variant_id: Option<i32>,
+    stock: i32,
+    reserved_stock: i32,
+    available_stock: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdjustStockResponse {
+    success: bool,
+    message: &'static str,
+    data: StockData,
+}
+#[utoipa::path(
+    post,
+    path = "/products/{id}/adjust-stock",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    request_body = AdjustStockRequest,
+    responses(
+        (status = 200, body = AdjustStockResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "Invalid reason, missing variant or stock would drop below reserved", body = ErrorResponse),
+        (status = 404, description = "Product or variant not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn adjust_stock(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(body): ApiJson<AdjustStockRequest>,
+) -> APIResponse<(StatusCode, Json<AdjustStockResponse>)> {
+    let AdjustStockRequest {
+        variant_id,
+        quantity,
+        reason,
+        note,
+    } = body;
+    let db = &state.conn;
+
+    let levels =
+        InventoryService::adjust(db, id, variant_id, current_user.id, quantity, reason, note)
+            .await?;
+    let data = StockData {
+        product_id: id,
+        variant_id,
+        stock: levels.stock,
+        reserved_stock: levels.reserved_stock,
+        available_stock: levels.stock - levels.reserved_stock,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(AdjustStockResponse {
+            success: true,
+            message: "Stock adjusted successfully",
+            data,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FindInventoryParams {
+    page: Option<i32>,
+    size: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FindInventoryResponse {
+    success: bool,
+    total_page: u64,
+    total_items: u64,
+    #[schema(value_type = Vec<InventoryMovement>)]
+    data: Vec<inventory_movement::Model>,
+}
+#[utoipa::path(
+    get,
+    path = "/products/{id}/inventory",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+        FindInventoryParams,
+    ),
+    responses(
+        (status = 200, body = FindInventoryResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn find_inventory(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+    ApiQuery(query): ApiQuery<FindInventoryParams>,
+) -> APIResponse<(StatusCode, Json<FindInventoryResponse>)> {
+    let FindInventoryParams { page, size } = query;
+    let db = &state.conn;
+
+    let (data, total_items, total_page) = InventoryService::history(db, id, page, size).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(FindInventoryResponse {
+            success: true,
+            total_page,
+            total_items,
+            data,
+        }),
+    ))
+}
+
+// Only documents the multipart body, the handler reads the parts itself;
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct UploadImageRequest {
+    /// JPEG, PNG or WebP file, no larger than the configured storage.max_image_bytes
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadImageResponse {
+    success: bool,
+    message: &'static str,
+    data: ImageData,
+}
+#[utoipa::path(
+    post,
+    path = "/products/{id}/images",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    request_body(content = UploadImageRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, body = UploadImageResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "Missing file, malformed multipart body or file not matching its content type", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 413, description = "Image too large", body = ErrorResponse),
+        (status = 415, description = "Not a JPEG, PNG or WebP image", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn upload_image(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+    ApiMultipart(mut multipart): ApiMultipart,
+) -> APIResponse<(StatusCode, Json<UploadImageResponse>)> {
+    let max_bytes = state.config.storage.max_image_bytes;
+
+    let mut field = multipart
+        .next_field()
+        .await?
+        .filter(|f| f.name() == Some("file"))
+        .ok_or(AppError::ImageRequired)?;
+
+    let content_type = field.content_type().unwrap_or_default().to_string();
+    ImageService::check_content_type(&content_type)?;
+
+    // The body limit is lifted for this route, the size is enforced here while the file streams in;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(AppError::ImageTooLarge(max_bytes));
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let image = ImageService::upload(
+        &state.conn,
+        state.storage(),
+        id,
+        &content_type,
+        bytes,
+        state.config.storage.thumbnail_size,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UploadImageResponse {
+            success: true,
+            message: "Image uploaded successfully",
+            data: ImageData::new(image, state.storage()),
+        }),
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FindImagesResponse {
+    success: bool,
+    data: Vec<ImageData>,
+}
+#[utoipa::path(
+    get,
+    path = "/products/{id}/images",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    responses(
+        (status = 200, body = FindImagesResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+    ),
+)]
+pub async fn find_images(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+) -> APIResponse<(StatusCode, Json<FindImagesResponse>)> {
+    let data = ImageService::find(&state.conn, id)
+        .await?
+        .into_iter()
+        .map(|image| ImageData::new(image, state.storage()))
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(FindImagesResponse {
+            success: true,
+            data,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReorderImagesRequest {
+    // Every image id of the product, in the new display order;
+    image_ids: Vec<i32>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/products/{id}/images/order",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+    ),
+    request_body = ReorderImagesRequest,
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 400, description = "The ids are not exactly the product's images", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn reorder_images(
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<i32>,
+    ApiJson(body): ApiJson<ReorderImagesRequest>,
+) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
+    ImageService::reorder(&state.conn, id, body.image_ids).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProductResponse {
+            success: true,
+            message: "Images reordered successfully".to_owned(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/products/{id}/images/{image_id}/primary",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+        ("image_id" = i32, Path, description = "Image id"),
+    ),
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Product or image not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn set_primary_image(
+    State(state): State<AppState>,
+    ApiPath((id, image_id)): ApiPath<(i32, i32)>,
+) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
+    ImageService::set_primary(&state.conn, id, image_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProductResponse {
+            success: true,
+            message: "Primary image updated successfully".to_owned(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/products/{id}/images/{image_id}",
+    tag = "products",
+    params(
+        ("id" = i32, Path, description = "Product id"),
+        ("image_id" = i32, Path, description = "Image id"),
+    ),
+    responses(
+        (status = 200, body = ProductResponse),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Product or image not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_image(
+    State(state): State<AppState>,
+    ApiPath((id, image_id)): ApiPath<(i32, i32)>,
+) -> APIResponse<(StatusCode, Json<ProductResponse>)> {
+    ImageService::delete(&state.conn, state.storage(), id, image_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProductResponse {
+            success: true,
+            message: "Image deleted successfully".to_owned(),
+        }),
+    ))
+}
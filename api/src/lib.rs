@@ -1,37 +1,53 @@
-use axum::http::Method;
-use axum::{Router, Server};
 use dotenvy::dotenv;
 use sea_orm::*;
-use std::env;
-use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
+use tracing_subscriber::EnvFilter;
 
 use ::migration::{Migrator, MigratorTrait};
 
+#[cfg(not(any(feature = "postgres", feature = "sqlite")))]
+compile_error!("Enable at least one database backend feature: `postgres` or `sqlite`");
+
+mod app;
+mod config;
 mod errors;
 mod extractor;
 mod handler;
 mod middlewares;
+mod openapi;
 mod routes;
 mod services;
+mod storage;
+#[cfg(test)]
+mod tests;
 mod utils;
 
-use routes::{auth_routes, brand_routes, cart_routes, category_routes, product_routes};
-
-#[derive(Debug, Clone)]
-pub struct AppState {
-    conn: DbConn,
-}
+pub use app::{build_router, AppBuilder, AppState};
+pub use config::{AppConfig, CascadePolicy, ConfigError, StorageBackend};
+pub use middlewares::{require_role, user_auth_required, CurrentUser};
+pub use openapi::ApiDoc;
+pub use storage::{LocalStorage, S3Storage, Storage, StorageError};
 
 #[tokio::main]
 pub async fn run() {
-    env::set_var("RUST_LOG", "debug");
-    tracing_subscriber::fmt::init();
-
     dotenv().ok();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env");
 
-    let conn = if let Ok(connection) = Database::connect(database_url).await {
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&config.server.log_filter))
+        .init();
+
+    if config.cors.allowed_origins.is_empty() {
+        tracing::warn!("No CORS origins configured, browsers will refuse cross-origin requests");
+    }
+
+    let conn = if let Ok(connection) = Database::connect(config.connect_options()).await {
         connection
     } else {
         eprintln!("Failed to connect to database");
@@ -43,25 +59,16 @@ pub async fn run() {
         std::process::exit(1);
     }
 
-    let app_state = AppState { conn };
-
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::PATCH])
-        .allow_origin(Any);
-
-    let root_router = Router::new()
-        .merge(auth_routes())
-        .merge(category_routes())
-        .merge(brand_routes())
-        .merge(product_routes())
-        .merge(cart_routes())
-        .with_state(app_state)
-        .layer(cors);
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], 6969));
+    let app_state = match AppState::new(conn).with_config(config) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    Server::bind(&addr)
-        .serve(root_router.into_make_service())
+    AppBuilder::new(app_state)
+        .serve()
         .await
         .expect("Failed to run server");
 }
@@ -1,35 +1,56 @@
 use axum::{
+    extract::State,
     headers::{authorization::Bearer, Authorization},
-    http::{Request, StatusCode},
+    http::Request,
     middleware::Next,
     response::Response,
-    TypedHeader,
+    Extension, TypedHeader,
 };
 
-use crate::utils::jwt::verify_token_middleware;
+use ::entity::sea_orm_active_enums::UserRole;
+
+use crate::errors::{APIResponse, AppError};
+use crate::utils::jwt::verify_token;
 
 #[derive(Clone, Debug)]
 pub struct CurrentUser {
     pub id: i32,
+    pub role: UserRole,
 }
 pub async fn user_auth_required<B>(
     token: Option<TypedHeader<Authorization<Bearer>>>,
     mut req: Request<B>,
     next: Next<B>,
-) -> Result<Response, (StatusCode, &'static str)> {
+) -> APIResponse<Response> {
     let token = if let Some(t) = token {
         t
     } else {
-        return Err((StatusCode::UNAUTHORIZED, "Token not provided"));
+        return Err(AppError::MissingToken);
     };
 
-    let user_claims = verify_token_middleware(token.token())?;
+    let user_claims = verify_token(token.token())?;
 
     let current_user = CurrentUser {
         id: user_claims.user_id,
+        role: user_claims.role,
     };
 
     req.extensions_mut().insert(current_user);
 
     Ok(next.run(req).await)
 }
+
+// Must be layered inside user_auth_required since it relies on the CurrentUser extension;
+// The required role is passed as the middleware state, e.g. from_fn_with_state(UserRole::Admin, require_role);
+pub async fn require_role<B>(
+    State(required_role): State<UserRole>,
+    Extension(current_user): Extension<CurrentUser>,
+    req: Request<B>,
+    next: Next<B>,
+) -> APIResponse<Response> {
+    if current_user.role != required_role {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(next.run(req).await)
+}
@@ -1,3 +1,5 @@
 pub mod auth_middleware;
+pub mod request_id;
 
 pub use auth_middleware::*;
+pub use request_id::*;
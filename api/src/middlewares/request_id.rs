@@ -0,0 +1,48 @@
+use axum::{
+    http::{header::HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::encryption::generate_random_string;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// The id of the request being handled, None outside of the request_id middleware (e.g. in background tasks);
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+// Reuses the caller's x-request-id when it looks sane so ids can be followed across services,
+// otherwise a new one is generated, either way it is echoed back in the response header;
+pub async fn request_id<B>(req: Request<B>, next: Next<B>) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(String::from)
+        .unwrap_or_else(|| generate_random_string(32));
+
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
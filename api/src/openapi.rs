@@ -0,0 +1,157 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use ::entity::sea_orm_active_enums::{InventoryReason, OrderStatus, UserRole};
+
+use crate::errors::ErrorResponse;
+use crate::handler::{auth, brand, cart, category, health, order, product};
+use crate::services::{
+    CartData, CategoryNode, FacetCount, ImageData, MigrationStatus, OrderData, OrderItemData,
+    OrderSummary, PriceBucket, ProductData, ProductFacets, SearchHit, VariantData,
+};
+use crate::utils::money::MoneySchema;
+
+// Generated at compile time from the #[utoipa::path] annotations on the handlers,
+// every route added in routes/ must be listed here as well (checked by tests::openapi);
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "axum-seaorm shop API"),
+    paths(
+        auth::register_user,
+        auth::login,
+        auth::persistent_login,
+        auth::refresh_token,
+        auth::logout,
+        brand::create_brand,
+        brand::find_brands,
+        brand::delete_brand,
+        brand::restore_brand,
+        category::create_category,
+        category::find_category,
+        category::delete_category,
+        category::restore_category,
+        category::category_tree,
+        category::category_breadcrumbs,
+        category::move_category,
+        product::create_product,
+        product::create_variant,
+        product::find_products,
+        product::update_product,
+        product::delete_product,
+        product::restore_product,
+        product::adjust_stock,
+        product::find_inventory,
+        product::upload_image,
+        product::find_images,
+        product::reorder_images,
+        product::set_primary_image,
+        product::delete_image,
+        cart::create_or_update_cart,
+        cart::find_carts,
+        order::checkout,
+        order::find_orders,
+        order::find_order,
+        order::update_order_status,
+        order::find_order_history,
+        health::liveness,
+        health::readiness,
+    ),
+    components(schemas(
+        ErrorResponse,
+        MoneySchema,
+        UserRole,
+        OrderStatus,
+        InventoryReason,
+        ::entity::brand::Model,
+        ::entity::category::Model,
+        ::entity::inventory_movement::Model,
+        ::entity::order_status_history::Model,
+        ProductData,
+        VariantData,
+        ImageData,
+        SearchHit,
+        ProductFacets,
+        FacetCount,
+        PriceBucket,
+        CategoryNode,
+        CartData,
+        OrderSummary,
+        OrderItemData,
+        OrderData,
+        MigrationStatus,
+        auth::RegisterRequest,
+        auth::RegisterResponse,
+        auth::LoginRequest,
+        auth::LoginResponse,
+        auth::RefreshTokenRequest,
+        auth::LogoutResponse,
+        auth::UserData,
+        auth::PersistentLoginResponse,
+        brand::BrandResponse,
+        brand::CreateBrandRequest,
+        brand::FindBrandsResponse,
+        brand::RestoreBrandResponse,
+        category::FindCategoryResponse,
+        category::CategoryResponse,
+        category::CreateCategoryRequest,
+        category::CategoryTreeResponse,
+        category::BreadcrumbsResponse,
+        category::MoveCategoryRequest,
+        product::ProductResponse,
+        product::CreateProductRequest,
+        product::CreateVariantData,
+        product::FindProductsResponse,
+        product::ProductSort,
+        product::UpdateProductData,
+        product::AdjustStockRequest,
+        product::StockData,
+        product::AdjustStockResponse,
+        product::FindInventoryResponse,
+        product::UploadImageRequest,
+        product::UploadImageResponse,
+        product::FindImagesResponse,
+        product::ReorderImagesRequest,
+        cart::CreateOrUpdateCartRequest,
+        cart::CreateOrUpdateCartResponse,
+        cart::FindCartResponse,
+        order::CheckoutResponse,
+        order::FindOrdersResponse,
+        order::FindOrderResponse,
+        order::UpdateOrderStatusRequest,
+        order::UpdateOrderStatusResponse,
+        order::FindOrderHistoryResponse,
+        health::LivenessResponse,
+        health::ReadinessResponse,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Registration, login and token rotation"),
+        (name = "brands"),
+        (name = "categories"),
+        (name = "products", description = "Catalog and inventory"),
+        (name = "carts", description = "Cart items backed by stock reservations"),
+        (name = "orders"),
+        (name = "health", description = "Liveness and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
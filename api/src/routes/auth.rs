@@ -11,6 +11,8 @@ pub fn auth_routes() -> Router<AppState> {
         Router::new()
             .route("/register", post(auth::register_user))
             .route("/login", post(auth::login))
-            .route("/persistent", get(auth::persistent_login)),
+            .route("/persistent", get(auth::persistent_login))
+            .route("/refresh", post(auth::refresh_token))
+            .route("/logout", post(auth::logout)),
     )
 }
@@ -4,8 +4,10 @@ use axum::{
     Router,
 };
 
+use ::entity::sea_orm_active_enums::UserRole;
+
 use crate::handler::brand;
-use crate::middlewares::user_auth_required;
+use crate::middlewares::{require_role, user_auth_required};
 use crate::AppState;
 
 pub fn brand_routes() -> Router<AppState> {
@@ -15,6 +17,10 @@ pub fn brand_routes() -> Router<AppState> {
             .route("/create", post(brand::create_brand))
             .route("/delete/:id", delete(brand::delete_brand))
             .route("/restore/:id", patch(brand::restore_brand))
+            .route_layer(middleware::from_fn_with_state(
+                UserRole::Admin,
+                require_role,
+            ))
             .route_layer(middleware::from_fn(user_auth_required))
             .route("/find", get(brand::find_brands)),
     )
@@ -4,8 +4,10 @@ use axum::{
     Router,
 };
 
+use ::entity::sea_orm_active_enums::UserRole;
+
 use crate::handler::category;
-use crate::middlewares::user_auth_required;
+use crate::middlewares::{require_role, user_auth_required};
 use crate::AppState;
 
 pub fn category_routes() -> Router<AppState> {
@@ -15,7 +17,14 @@ pub fn category_routes() -> Router<AppState> {
             .route("/create", post(category::create_category))
             .route("/delete/:id", delete(category::delete_category))
             .route("/restore/:id", patch(category::restore_category))
+            .route("/move/:id", patch(category::move_category))
+            .route_layer(middleware::from_fn_with_state(
+                UserRole::Admin,
+                require_role,
+            ))
             .route_layer(middleware::from_fn(user_auth_required))
-            .route("/find", get(category::find_category)),
+            .route("/find", get(category::find_category))
+            .route("/tree", get(category::category_tree))
+            .route("/breadcrumbs/:id", get(category::category_breadcrumbs)),
     )
 }
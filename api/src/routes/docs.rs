@@ -0,0 +1,12 @@
+use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{openapi::ApiDoc, AppState};
+
+// Serves the generated document at /openapi.json and a Swagger UI page at /docs;
+pub fn docs_routes() -> Router<AppState> {
+    SwaggerUi::new("/docs")
+        .url("/openapi.json", ApiDoc::openapi())
+        .into()
+}
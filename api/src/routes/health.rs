@@ -0,0 +1,12 @@
+use axum::{routing::get, Router};
+
+use crate::{handler::health, AppState};
+
+pub fn health_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/health",
+        Router::new()
+            .route("/live", get(health::liveness))
+            .route("/ready", get(health::readiness)),
+    )
+}
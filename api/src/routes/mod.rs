@@ -2,10 +2,18 @@ pub mod auth;
 pub mod brand;
 pub mod cart;
 pub mod category;
+pub mod docs;
+pub mod health;
+pub mod order;
 pub mod product;
+pub mod uploads;
 
 pub use auth::*;
 pub use brand::*;
 pub use cart::*;
 pub use category::*;
+pub use docs::*;
+pub use health::*;
+pub use order::*;
 pub use product::*;
+pub use uploads::*;
@@ -0,0 +1,20 @@
+use axum::{
+    middleware,
+    routing::{get, patch, post},
+    Router,
+};
+
+use crate::{handler::order, middlewares::user_auth_required, AppState};
+
+pub fn order_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/orders",
+        Router::new()
+            .route("/checkout", post(order::checkout))
+            .route("/find", get(order::find_orders))
+            .route("/:id", get(order::find_order))
+            .route("/:id/status", patch(order::update_order_status))
+            .route("/:id/history", get(order::find_order_history))
+            .route_layer(middleware::from_fn(user_auth_required)),
+    )
+}
@@ -1,8 +1,10 @@
 use axum::routing::{delete, get, patch, post};
-use axum::{middleware, Router};
+use axum::{extract::DefaultBodyLimit, middleware, Router};
+
+use ::entity::sea_orm_active_enums::UserRole;
 
 use crate::handler::product;
-use crate::middlewares::user_auth_required;
+use crate::middlewares::{require_role, user_auth_required};
 use crate::AppState;
 
 pub fn product_routes() -> Router<AppState> {
@@ -13,7 +15,25 @@ pub fn product_routes() -> Router<AppState> {
             .route("/delete/:id", delete(product::delete_product))
             .route("/restore/:id", patch(product::restore_product))
             .route("/update/:id", patch(product::update_product))
+            .route("/:id/inventory", get(product::find_inventory))
+            .route("/:id/adjust-stock", post(product::adjust_stock))
+            .route("/:id/variants", post(product::create_variant))
+            .route(
+                "/:id/images",
+                post(product::upload_image).layer(DefaultBodyLimit::disable()),
+            )
+            .route("/:id/images/order", patch(product::reorder_images))
+            .route(
+                "/:id/images/:image_id/primary",
+                patch(product::set_primary_image),
+            )
+            .route("/:id/images/:image_id", delete(product::delete_image))
+            .route_layer(middleware::from_fn_with_state(
+                UserRole::Admin,
+                require_role,
+            ))
             .route_layer(middleware::from_fn(user_auth_required))
-            .route("/find", get(product::find_products)),
+            .route("/find", get(product::find_products))
+            .route("/:id/images", get(product::find_images)),
     )
 }
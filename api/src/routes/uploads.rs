@@ -0,0 +1,20 @@
+use axum::{routing::get_service, Router};
+use std::io;
+use tower_http::services::ServeDir;
+
+use crate::config::{StorageBackend, StorageConfig};
+use crate::errors::AppError;
+use crate::storage::LOCAL_STORAGE_ROUTE;
+use crate::AppState;
+
+// Serves the files of the local storage backend, S3 objects are downloaded from the bucket instead;
+pub fn upload_routes(config: &StorageConfig) -> Router<AppState> {
+    match config.backend {
+        StorageBackend::Local => Router::new().nest_service(
+            LOCAL_STORAGE_ROUTE,
+            get_service(ServeDir::new(&config.local_dir))
+                .handle_error(|e: io::Error| async move { AppError::StorageError(e.into()) }),
+        ),
+        StorageBackend::S3 => Router::new(),
+    }
+}
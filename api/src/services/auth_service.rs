@@ -1,9 +1,19 @@
-use ::entity::{prelude::User, user};
-use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, QueryFilter, Set};
+use ::entity::{
+    prelude::{RefreshToken, User},
+    refresh_token,
+    sea_orm_active_enums::UserRole,
+    user,
+};
+use chrono::{Duration, Utc};
+use migration::{Expr, USER_EMAIL_INDEX, USER_USERNAME_INDEX};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn,
+    EntityTrait, QueryFilter, Set, TransactionTrait,
+};
 
+use super::unique_violation;
 use crate::errors::{APIResult, AppError};
-use crate::utils::encryption::validate_password;
+use crate::utils::encryption::{generate_random_string, hash_refresh_token, validate_password};
 
 pub struct AuthService;
 
@@ -14,34 +24,26 @@ impl AuthService {
         email: String,
         password: String,
     ) -> APIResult<user::Model> {
-        if (User::find()
-            .filter(user::Column::Username.eq(username.as_str()))
-            .one(db)
-            .await?)
-            .is_some()
-        {
-            return Err(AppError::DuplicateUsername);
-        }
-
-        if (User::find()
-            .filter(user::Column::Email.eq(email.as_str()))
-            .one(db)
-            .await?)
-            .is_some()
-        {
-            return Err(AppError::DuplicateEmail);
-        }
-
-        Ok(user::ActiveModel {
+        user::ActiveModel {
             username: Set(username),
             email: Set(email),
             password: Set(password),
+            role: Set(UserRole::Customer),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
             ..Default::default()
         }
         .insert(db)
-        .await?)
+        .await
+        .map_err(|e| {
+            unique_violation(
+                e,
+                [
+                    (USER_USERNAME_INDEX, AppError::DuplicateUsername),
+                    (USER_EMAIL_INDEX, AppError::DuplicateEmail),
+                ],
+            )
+        })
     }
 
     pub async fn login_user(
@@ -76,4 +78,119 @@ impl AuthService {
             Err(AppError::InvalidToken)
         }
     }
+
+    // Issues a new opaque refresh token, starting a new token family unless one is given (rotation);
+    // Only the token hash is stored, the plain token is returned once to the client;
+    pub async fn issue_refresh_token<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        family_id: Option<String>,
+        ttl: Duration,
+    ) -> APIResult<String> {
+        let token = generate_random_string(64);
+        let family_id = family_id.unwrap_or_else(|| generate_random_string(32));
+
+        refresh_token::ActiveModel {
+            user_id: Set(user_id),
+            family_id: Set(family_id),
+            token_hash: Set(hash_refresh_token(&token)),
+            expires_at: Set((Utc::now() + ttl).into()),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(token)
+    }
+
+    // Exchanges a refresh token for a new one of the same family;
+    // Presenting an already rotated token means it was leaked, so the whole family gets revoked;
+    pub async fn rotate_refresh_token(
+        db: &DbConn,
+        token: String,
+        ttl: Duration,
+    ) -> APIResult<(user::Model, String)> {
+        let txn = db.begin().await?;
+
+        let stored = RefreshToken::find()
+            .filter(refresh_token::Column::TokenHash.eq(hash_refresh_token(&token)))
+            .one(&txn)
+            .await?;
+
+        let stored = if let Some(t) = stored {
+            t
+        } else {
+            return Err(AppError::InvalidRefreshToken);
+        };
+
+        if stored.revoked_at.is_some() {
+            Self::revoke_token_family(&txn, &stored.family_id).await?;
+            txn.commit().await?;
+
+            return Err(AppError::RefreshTokenReused);
+        }
+
+        if stored.expires_at < Utc::now() {
+            return Err(AppError::InvalidRefreshToken);
+        }
+
+        // Conditional update so two concurrent refreshes with the same token cannot both succeed;
+        let revoked = RefreshToken::update_many()
+            .col_expr(
+                refresh_token::Column::RevokedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(refresh_token::Column::Id.eq(stored.id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        if revoked.rows_affected == 0 {
+            Self::revoke_token_family(&txn, &stored.family_id).await?;
+            txn.commit().await?;
+
+            return Err(AppError::RefreshTokenReused);
+        }
+
+        let user = if let Some(u) = User::find_by_id(stored.user_id).one(&txn).await? {
+            u
+        } else {
+            return Err(AppError::InvalidRefreshToken);
+        };
+
+        let new_token =
+            Self::issue_refresh_token(&txn, user.id, Some(stored.family_id), ttl).await?;
+
+        txn.commit().await?;
+
+        Ok((user, new_token))
+    }
+
+    pub async fn revoke_refresh_token(db: &DbConn, token: String) -> APIResult<()> {
+        let stored = RefreshToken::find()
+            .filter(refresh_token::Column::TokenHash.eq(hash_refresh_token(&token)))
+            .one(db)
+            .await?;
+
+        if let Some(t) = stored {
+            Self::revoke_token_family(db, &t.family_id).await
+        } else {
+            Err(AppError::InvalidRefreshToken)
+        }
+    }
+
+    async fn revoke_token_family<C: ConnectionTrait>(db: &C, family_id: &str) -> APIResult<()> {
+        RefreshToken::update_many()
+            .col_expr(
+                refresh_token::Column::RevokedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(refresh_token::Column::FamilyId.eq(family_id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
 }
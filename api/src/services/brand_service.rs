@@ -1,112 +1,89 @@
 use chrono::Utc;
-use migration::{Condition, Expr, Func};
+use migration::{Condition, BRAND_NAME_INDEX};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, IntoActiveModel, ItemsAndPagesNumber,
-    PaginatorTrait, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, QueryFilter, Set, TransactionTrait,
 };
 
 use ::entity::{brand, prelude::Brand};
 
-use super::{page_matcher, size_matcher};
-use crate::errors::{APIResult, AppError};
+use super::{
+    contains_ignore_case, paginate, unique_violation, KeyKind, Keyset, Page, PageRequest,
+    ProductParent, ProductService, SoftDeleteService,
+};
+use crate::{
+    config::CascadePolicy,
+    errors::{APIResult, AppError},
+};
 
 pub struct BrandService;
 
 impl BrandService {
     pub async fn create(db: &DbConn, name: String) -> APIResult<brand::Model> {
-        if (Brand::find()
-            .filter(brand::Column::Name.eq(name.as_str()))
-            .one(db)
-            .await?)
-            .is_some()
-        {
-            return Err(AppError::DuplicateBrand);
-        }
-
-        Ok(brand::ActiveModel {
+        brand::ActiveModel {
             name: Set(name),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
             ..Default::default()
         }
         .insert(db)
-        .await?)
+        .await
+        .map_err(|e| unique_violation(e, [(BRAND_NAME_INDEX, AppError::DuplicateBrand)]))
     }
 
     pub async fn get(
         db: &DbConn,
         keyword: Option<String>,
-        page: Option<i32>,
-        size: Option<i32>,
         all: Option<bool>,
-    ) -> APIResult<(Vec<brand::Model>, u64, u64)> {
+        request: PageRequest,
+    ) -> APIResult<Page<brand::Model>> {
         let mut condition = Condition::all();
 
         if let Some(k) = keyword {
-            let like = format!("%{}%", k.to_lowercase());
-
-            condition =
-                condition.add(Expr::expr(Func::lower(Expr::col(brand::Column::Name))).like(like));
+            condition = condition.add(contains_ignore_case(brand::Column::Name, &k));
         }
 
         if all.is_none() {
             condition = condition.add(brand::Column::DeletedAt.is_null());
         }
 
-        let size = size_matcher(size)?;
-        let page = page_matcher(page)?;
-
-        let ItemsAndPagesNumber {
-            number_of_items,
-            number_of_pages,
-        } = Brand::find()
-            .filter(condition.clone())
-            .paginate(db, size)
-            .num_items_and_pages()
-            .await?;
-        let data = Brand::find()
-            .filter(condition)
-            .paginate(db, size)
-            .fetch_page(page)
-            .await?;
-
-        Ok((data, number_of_items, number_of_pages))
+        paginate(
+            db,
+            Brand::find().filter(condition),
+            Keyset::new("brands").asc(brand::Column::Id, KeyKind::Int),
+            request,
+        )
+        .await
     }
 
-    pub async fn delete(db: &DbConn, id: i32) -> APIResult<()> {
-        let brand = Brand::find_by_id(id).one(db).await?;
+    pub async fn delete(db: &DbConn, id: i32, policy: CascadePolicy) -> APIResult<()> {
+        let txn = db.begin().await?;
 
-        let mut brand = if let Some(brand) = brand {
-            if brand.deleted_at.is_none() {
-                brand.into_active_model()
-            } else {
-                return Err(AppError::BrandAlreadyDeleted);
-            }
-        } else {
-            return Err(AppError::BrandNotFound);
-        };
+        let deleted = SoftDeleteService::soft_delete::<Brand>(&txn, id).await?;
 
-        brand.deleted_at = Set(Some(Utc::now().into()));
-        brand.update(db).await?;
+        if let Some(deleted_at) = deleted.deleted_at {
+            ProductService::parent_deleted(&txn, ProductParent::Brand, id, deleted_at, policy)
+                .await?;
+        }
+
+        txn.commit().await?;
 
         Ok(())
     }
 
+    // Products cascaded by the delete come back as well, whatever the current policy;
     pub async fn restore(db: &DbConn, id: i32) -> APIResult<()> {
-        let brand = Brand::find_by_id(id).one(db).await?;
-
-        let mut brand = if let Some(b) = brand {
-            if b.deleted_at.is_none() {
-                return Err(AppError::CannotRestoreBrand);
-            } else {
-                b.into_active_model()
-            }
-        } else {
-            return Err(AppError::BrandNotFound);
-        };
-
-        brand.deleted_at = Set(None);
-        brand.update(db).await?;
+        let txn = db.begin().await?;
+
+        let deleted_at = SoftDeleteService::find_with_deleted::<Brand>(&txn, id)
+            .await?
+            .deleted_at;
+        SoftDeleteService::restore::<Brand>(&txn, id).await?;
+
+        if let Some(deleted_at) = deleted_at {
+            ProductService::parent_restored(&txn, ProductParent::Brand, id, deleted_at).await?;
+        }
+
+        txn.commit().await?;
 
         Ok(())
     }
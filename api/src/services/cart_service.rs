@@ -1,135 +1,188 @@
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use migration::{Condition, Expr, JoinType};
 use sea_orm::{
     prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, DbConn, EntityTrait,
-    FromQueryResult, IntoActiveModel, ItemsAndPagesNumber, PaginatorTrait, QueryFilter,
-    QuerySelect, RelationTrait, Set,
+    FromQueryResult, IntoActiveModel, QueryFilter, QuerySelect, RelationTrait, Set,
+    TransactionTrait,
 };
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use ::entity::{
     brand, cart, category,
     prelude::{Cart, Product, User},
-    product,
+    product, product_variant,
 };
 
-use super::{page_matcher, size_matcher};
+use super::{
+    paginate, KeyKind, Keyset, Page, PageRequest, ReservationService, SoftDeleteService, StockItem,
+};
 use crate::errors::{APIResult, AppError};
+use crate::utils::money::Money;
 
-#[derive(Debug, Serialize, FromQueryResult)]
+#[derive(Debug, FromQueryResult)]
+struct CartRow {
+    id: i32,
+    quantity: i32,
+    product_id: i32,
+    variant_id: Option<i32>,
+    sku: Option<String>,
+    variant_price: Option<i64>,
+    variant_stock: Option<i32>,
+    product_category: String,
+    product_brand: String,
+    product_name: String,
+    product_price: i64,
+    product_currency: String,
+    product_stock: i32,
+    product_deleted_at: Option<DateTimeWithTimeZone>,
+    brand_deleted_at: Option<DateTimeWithTimeZone>,
+    category_deleted_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CartData {
     id: i32,
     quantity: i32,
     product_id: i32,
+    variant_id: Option<i32>,
+    sku: Option<String>,
     product_category: String,
     product_brand: String,
     product_name: String,
-    product_price: i32,
+    product_price: Money,
     product_stock: i32,
-    subtotal: i32,
+    subtotal: Money,
+    #[schema(value_type = Option<String>, format = DateTime)]
     product_deleted_at: Option<DateTimeWithTimeZone>,
+    #[schema(value_type = Option<String>, format = DateTime)]
     brand_deleted_at: Option<DateTimeWithTimeZone>,
+    #[schema(value_type = Option<String>, format = DateTime)]
     category_deleted_at: Option<DateTimeWithTimeZone>,
 }
 
+impl TryFrom<CartRow> for CartData {
+    type Error = AppError;
+
+    // The subtotal is computed here with overflow checks instead of in SQL;
+    // Price and stock are the variant's when the line is for one, falling back to the product's price;
+    fn try_from(row: CartRow) -> APIResult<Self> {
+        let product_price = Money::new(
+            row.variant_price.unwrap_or(row.product_price),
+            row.product_currency,
+        );
+        let subtotal = product_price.checked_mul(row.quantity)?;
+
+        Ok(Self {
+            id: row.id,
+            quantity: row.quantity,
+            product_id: row.product_id,
+            variant_id: row.variant_id,
+            sku: row.sku,
+            product_category: row.product_category,
+            product_brand: row.product_brand,
+            product_name: row.product_name,
+            product_price,
+            product_stock: row.variant_stock.unwrap_or(row.product_stock),
+            subtotal,
+            product_deleted_at: row.product_deleted_at,
+            brand_deleted_at: row.brand_deleted_at,
+            category_deleted_at: row.category_deleted_at,
+        })
+    }
+}
+
 pub struct CartService;
 
 impl CartService {
+    // Cart quantities are backed by a stock reservation, so the stock is held until checkout or expiry;
+    // A product with variants is added through one of them, each variant being its own cart line;
     pub async fn create_or_update(
         db: &DbConn,
         user_id: i32,
         product_id: i32,
+        variant_id: Option<i32>,
         quantity: i32,
+        reservation_ttl: Duration,
     ) -> APIResult<&'static str> {
-        let cart_product = Product::find_by_id(product_id).one(db).await?;
-
-        if cart_product.is_none() {
-            return Err(AppError::ProductNotFound);
-        }
-
-        let cart_product = cart_product.unwrap();
-
         if quantity < 1 {
             return Err(AppError::InvalidQuantity);
         }
 
-        if quantity > cart_product.stock {
-            return Err(AppError::InsufficientStock);
-        }
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+        let item = StockItem::of(db, product_id, variant_id).await?;
 
         if (User::find_by_id(user_id).one(db).await?).is_none() {
             return Err(AppError::UserNotFound);
         }
 
+        let txn = db.begin().await?;
+
+        ReservationService::reserve(&txn, user_id, item, quantity, reservation_ttl).await?;
+
         let condition = Condition::all()
             .add(Expr::col(cart::Column::UserId).eq(user_id))
-            .add(Expr::col(cart::Column::ProductId).eq(product_id));
+            .add(Expr::col(cart::Column::ProductId).eq(product_id))
+            .add(item.variant_condition(cart::Column::VariantId));
+
+        let user_cart = Cart::find().filter(condition).one(&txn).await?;
 
-        let user_cart = Cart::find().filter(condition).one(db).await?;
+        let message = if let Some(user_cart) = user_cart {
+            let mut user_cart = user_cart.into_active_model();
+
+            user_cart.quantity = Set(quantity);
+            user_cart.updated_at = Set(Utc::now().into());
+            user_cart.update(&txn).await?;
 
-        if user_cart.is_none() {
+            "Cart updated successfully!"
+        } else {
             cart::ActiveModel {
                 user_id: Set(user_id),
                 product_id: Set(product_id),
+                variant_id: Set(variant_id),
                 quantity: Set(quantity),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
                 ..Default::default()
             }
-            .insert(db)
+            .insert(&txn)
             .await?;
 
-            Ok("Cart created successfully!")
-        } else {
-            let mut user_cart = user_cart.unwrap().into_active_model();
+            "Cart created successfully!"
+        };
 
-            user_cart.quantity = Set(quantity);
-            user_cart.updated_at = Set(Utc::now().into());
-            user_cart.update(db).await?;
+        txn.commit().await?;
 
-            Ok("Cart updated successfully!")
-        }
+        Ok(message)
     }
 
-    pub async fn get(
-        db: &DbConn,
-        user_id: i32,
-        page: Option<i32>,
-        size: Option<i32>,
-    ) -> APIResult<(Vec<CartData>, u64, u64)> {
-        let page = page_matcher(page)?;
-        let size = size_matcher(size)?;
-
-        let ItemsAndPagesNumber {
-            number_of_items,
-            number_of_pages,
-        } = Cart::find()
-            .filter(cart::Column::UserId.eq(user_id))
-            .left_join(Product)
-            .join_rev(JoinType::LeftJoin, category::Relation::Product.def())
-            .join_rev(JoinType::LeftJoin, brand::Relation::Product.def())
-            .paginate(db, size)
-            .num_items_and_pages()
-            .await?;
-        let data = Cart::find()
+    pub async fn get(db: &DbConn, user_id: i32, request: PageRequest) -> APIResult<Page<CartData>> {
+        let query = Cart::find()
             .filter(cart::Column::UserId.eq(user_id))
             .left_join(Product)
             .column_as(product::Column::Name, "product_name")
             .column_as(product::Column::Price, "product_price")
+            .column_as(product::Column::Currency, "product_currency")
             .column_as(product::Column::Stock, "product_stock")
+            .join(JoinType::LeftJoin, cart::Relation::ProductVariant.def())
+            .column_as(product_variant::Column::Sku, "sku")
+            .column_as(product_variant::Column::Price, "variant_price")
+            .column_as(product_variant::Column::Stock, "variant_stock")
             .join_rev(JoinType::LeftJoin, category::Relation::Product.def())
             .column_as(category::Column::Name, "product_category")
             .join_rev(JoinType::LeftJoin, brand::Relation::Product.def())
             .column_as(brand::Column::Name, "product_brand")
-            .column_as(Expr::cust("(cart.quantity * product.price)"), "subtotal")
             .column_as(product::Column::DeletedAt, "product_deleted_at")
             .column_as(brand::Column::DeletedAt, "brand_deleted_at")
-            .column_as(category::Column::DeletedAt, "category_deleted_at")
-            .into_model::<CartData>()
-            .paginate(db, size)
-            .fetch_page(page)
-            .await?;
-
-        Ok((data, number_of_items, number_of_pages))
+            .column_as(category::Column::DeletedAt, "category_deleted_at");
+
+        paginate::<_, CartRow>(
+            db,
+            query,
+            Keyset::new("carts").asc(cart::Column::Id, KeyKind::Int),
+            request,
+        )
+        .await?
+        .try_map(CartData::try_from)
     }
 }
@@ -1,119 +1,278 @@
 use chrono::Utc;
-use migration::{Condition, Expr, Func};
+use migration::{Condition, CATEGORY_NAME_INDEX};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, IntoActiveModel, ItemsAndPagesNumber,
-    PaginatorTrait, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, IntoActiveModel,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
 };
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
 
-use ::entity::{category, prelude::Category};
+use ::entity::{category, prelude::Category, soft_delete::SoftDelete};
 
-use super::{page_matcher, size_matcher};
-use crate::errors::{APIResult, AppError};
+use super::{
+    contains_ignore_case, paginate, unique_violation, KeyKind, Keyset, Page, PageRequest,
+    ProductParent, ProductService, SoftDeleteService,
+};
+use crate::{
+    config::CascadePolicy,
+    errors::{APIResult, AppError},
+};
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CategoryNode {
+    id: i32,
+    name: String,
+    children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    fn build(model: category::Model, children: &mut HashMap<i32, Vec<category::Model>>) -> Self {
+        let nested = children
+            .remove(&model.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build(child, children))
+            .collect();
+
+        Self {
+            id: model.id,
+            name: model.name,
+            children: nested,
+        }
+    }
+}
 
 pub struct CategoryService;
 
 impl CategoryService {
-    pub async fn create(db: &DbConn, name: String) -> APIResult<category::Model> {
-        if (Category::find()
-            .filter(category::Column::Name.eq(name.as_str()))
-            .one(db)
-            .await?)
-            .is_some()
-        {
-            return Err(AppError::DuplicateCategory);
+    pub async fn create(
+        db: &DbConn,
+        name: String,
+        parent_id: Option<i32>,
+    ) -> APIResult<category::Model> {
+        if let Some(p) = parent_id {
+            SoftDeleteService::find_active::<Category>(db, p).await?;
         }
 
-        Ok(category::ActiveModel {
+        category::ActiveModel {
             name: Set(name),
+            parent_id: Set(parent_id),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
             ..Default::default()
         }
         .insert(db)
-        .await?)
+        .await
+        .map_err(|e| unique_violation(e, [(CATEGORY_NAME_INDEX, AppError::DuplicateCategory)]))
     }
 
     pub async fn get(
         db: &DbConn,
         keyword: Option<String>,
         all: Option<bool>,
-        page: Option<i32>,
-        size: Option<i32>,
-    ) -> APIResult<(Vec<category::Model>, u64, u64)> {
+        request: PageRequest,
+    ) -> APIResult<Page<category::Model>> {
         let mut condition = Condition::all();
 
         if let Some(k) = keyword {
-            let like = format!("%{}%", k.to_lowercase());
-
-            condition = condition
-                .add(Expr::expr(Func::lower(Expr::col(category::Column::Name))).like(like));
+            condition = condition.add(contains_ignore_case(category::Column::Name, &k));
         }
 
         if all.is_none() {
             condition = condition.add(category::Column::DeletedAt.is_null());
         }
 
-        let size = size_matcher(size)?;
-        let page = page_matcher(page)?;
+        paginate(
+            db,
+            Category::find().filter(condition),
+            Keyset::new("categories").asc(category::Column::Id, KeyKind::Int),
+            request,
+        )
+        .await
+    }
 
-        let ItemsAndPagesNumber {
-            number_of_items,
-            number_of_pages,
-        } = Category::find()
-            .filter(condition.clone())
-            .paginate(db, size)
-            .num_items_and_pages()
-            .await?;
-        let data = Category::find()
-            .filter(condition)
-            .paginate(db, size)
-            .fetch_page(page)
+    // The active categories nested by parent, siblings sorted by name;
+    // Restore refuses a category under a deleted parent, the root fallback only covers rows left so before that check;
+    pub async fn tree(db: &DbConn) -> APIResult<Vec<CategoryNode>> {
+        let categories = Category::find_active()
+            .order_by_asc(category::Column::Name)
+            .all(db)
             .await?;
+        let active: HashSet<i32> = categories.iter().map(|c| c.id).collect();
+
+        let mut roots = Vec::new();
+        let mut children: HashMap<i32, Vec<category::Model>> = HashMap::new();
+
+        for category in categories {
+            match category.parent_id.filter(|p| active.contains(p)) {
+                Some(p) => children.entry(p).or_default().push(category),
+                None => roots.push(category),
+            }
+        }
 
-        Ok((data, number_of_items, number_of_pages))
+        Ok(roots
+            .into_iter()
+            .map(|root| CategoryNode::build(root, &mut children))
+            .collect())
     }
 
-    pub async fn delete(db: &DbConn, id: i32) -> APIResult<()> {
-        let category = Category::find_by_id(id).one(db).await?;
+    // The category and its ancestors, root first;
+    pub async fn breadcrumbs(db: &DbConn, id: i32) -> APIResult<Vec<category::Model>> {
+        let category = SoftDeleteService::find_active::<Category>(db, id).await?;
 
-        let mut category = if let Some(c) = category {
-            if c.deleted_at.is_none() {
-                c.into_active_model()
-            } else {
-                return Err(AppError::CategoryAlreadyDeleted);
+        // A deleted ancestor ends the trail, restore refuses to revive a category under one anyway;
+        let mut categories: HashMap<i32, category::Model> = Category::find_active()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|c| (c.id, c))
+            .collect();
+
+        let mut parent_id = category.parent_id;
+        let mut crumbs = vec![category];
+
+        while let Some(parent) = parent_id.and_then(|p| categories.remove(&p)) {
+            parent_id = parent.parent_id;
+            crumbs.push(parent);
+        }
+
+        crumbs.reverse();
+
+        Ok(crumbs)
+    }
+
+    // Moves the category, with everything under it, below `parent_id` or to the root when None;
+    pub async fn move_to(db: &DbConn, id: i32, parent_id: Option<i32>) -> APIResult<()> {
+        let txn = db.begin().await?;
+
+        let category = SoftDeleteService::find_active::<Category>(&txn, id).await?;
+
+        if let Some(p) = parent_id {
+            SoftDeleteService::find_active::<Category>(&txn, p).await?;
+
+            if Self::ancestors(&txn, p).await?.contains(&id) {
+                return Err(AppError::InvalidCategoryParent);
             }
-        } else {
-            return Err(AppError::CategoryNotFound);
-        };
+        }
+
+        let mut category = category.into_active_model();
+        category.parent_id = Set(parent_id);
+        category.updated_at = Set(Utc::now().into());
+        category.update(&txn).await?;
 
-        category.deleted_at = Set(Some(Utc::now().into()));
-        category.update(db).await?;
+        txn.commit().await?;
 
         Ok(())
     }
 
-    #[allow(dead_code)] // for now;
-    pub async fn hard_delete(db: &DbConn, id: i32) -> APIResult<String> {
-        Category::delete_by_id(id).exec(db).await?;
+    // The active categories and all their active descendants, for filtering products by whole subtrees;
+    // The table is read once however many ids are asked for, an id shared by two subtrees is listed once;
+    pub async fn subtree_ids(db: &impl ConnectionTrait, ids: &[i32]) -> APIResult<Vec<i32>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut active = HashSet::new();
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
 
-        Ok("Category deleted successfully".to_string())
+        for category in Category::find_active().all(db).await? {
+            active.insert(category.id);
+
+            if let Some(p) = category.parent_id {
+                children.entry(p).or_default().push(category.id);
+            }
+        }
+
+        if !ids.iter().all(|id| active.contains(id)) {
+            return Err(AppError::CategoryNotFound);
+        }
+
+        let mut seen = HashSet::new();
+        let mut subtrees: Vec<i32> = ids.iter().copied().filter(|&id| seen.insert(id)).collect();
+        let mut next = 0;
+
+        while let Some(&current) = subtrees.get(next) {
+            subtrees.extend(
+                children
+                    .remove(&current)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|&id| seen.insert(id)),
+            );
+            next += 1;
+        }
+
+        Ok(subtrees)
     }
 
+    // `id` followed by its parent, grandparent and so on, deleted categories included;
+    async fn ancestors(db: &impl ConnectionTrait, id: i32) -> APIResult<Vec<i32>> {
+        let mut parents: HashMap<i32, Option<i32>> = Category::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|c| (c.id, c.parent_id))
+            .collect();
+
+        let mut ancestors = vec![id];
+        let mut current = id;
+
+        while let Some(Some(parent)) = parents.remove(&current) {
+            ancestors.push(parent);
+            current = parent;
+        }
+
+        Ok(ancestors)
+    }
+
+    pub async fn delete(db: &DbConn, id: i32, policy: CascadePolicy) -> APIResult<()> {
+        let txn = db.begin().await?;
+
+        let deleted = SoftDeleteService::soft_delete::<Category>(&txn, id).await?;
+
+        let subcategories = Category::find_active()
+            .filter(category::Column::ParentId.eq(id))
+            .count(&txn)
+            .await?;
+
+        if subcategories > 0 {
+            return Err(AppError::CategoryHasSubcategories);
+        }
+
+        if let Some(deleted_at) = deleted.deleted_at {
+            ProductService::parent_deleted(&txn, ProductParent::Category, id, deleted_at, policy)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    // Products cascaded by the delete come back as well, whatever the current policy;
+    // A subcategory only comes back once its parent has;
     pub async fn restore(db: &DbConn, id: i32) -> APIResult<()> {
-        let category = Category::find_by_id(id).one(db).await?;
+        let txn = db.begin().await?;
+
+        let category = SoftDeleteService::find_with_deleted::<Category>(&txn, id).await?;
+        let deleted_at = category.deleted_at;
 
-        let mut category = if let Some(c) = category {
-            if c.deleted_at.is_none() {
-                return Err(AppError::CannotRestoreCategory);
-            } else {
-                c.into_active_model()
+        if let Some(parent_id) = category.parent_id.filter(|_| deleted_at.is_some()) {
+            let parent = SoftDeleteService::find_with_deleted::<Category>(&txn, parent_id).await?;
+
+            if parent.deleted_at.is_some() {
+                return Err(AppError::CategoryParentDeleted);
             }
-        } else {
-            return Err(AppError::CategoryNotFound);
-        };
+        }
+
+        SoftDeleteService::restore::<Category>(&txn, id).await?;
+
+        if let Some(deleted_at) = deleted_at {
+            ProductService::parent_restored(&txn, ProductParent::Category, id, deleted_at).await?;
+        }
 
-        category.deleted_at = Set(None);
-        category.update(db).await?;
+        txn.commit().await?;
 
         Ok(())
     }
@@ -0,0 +1,53 @@
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, DbConn, Statement};
+use serde::Serialize;
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use crate::errors::APIResult;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MigrationStatus {
+    applied: usize,
+    pending: Vec<String>,
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+pub struct HealthService;
+
+impl HealthService {
+    pub async fn ping(db: &DbConn) -> APIResult<()> {
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT 1".to_owned(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    // Compares the migrations compiled into the binary with the ones recorded in seaql_migrations;
+    pub async fn migration_status(db: &DbConn) -> APIResult<MigrationStatus> {
+        let applied: HashSet<String> = Migrator::get_migration_models(db)
+            .await?
+            .into_iter()
+            .map(|m| m.version)
+            .collect();
+
+        let pending = Migrator::migrations()
+            .iter()
+            .map(|m| m.name().to_string())
+            .filter(|name| !applied.contains(name))
+            .collect();
+
+        Ok(MigrationStatus {
+            applied: applied.len(),
+            pending,
+        })
+    }
+}
@@ -0,0 +1,369 @@
+use chrono::Utc;
+use image::{
+    io::{Limits, Reader as ImageReader},
+    DynamicImage, ImageFormat, ImageOutputFormat,
+};
+use migration::PRODUCT_IMAGE_PRIMARY_INDEX;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::Serialize;
+use std::{collections::HashMap, io::Cursor};
+use tokio::sync::oneshot;
+use utoipa::ToSchema;
+
+use ::entity::{
+    prelude::{Product, ProductImage},
+    product_image,
+};
+
+use super::{is_unique_violation, SoftDeleteService};
+use crate::{
+    errors::{APIResult, AppError},
+    storage::Storage,
+    utils::encryption::generate_random_string,
+};
+
+// Content types accepted for an upload, with the format the bytes must decode as and the file extension;
+const ACCEPTED_TYPES: [(&str, ImageFormat, &str); 3] = [
+    ("image/jpeg", ImageFormat::Jpeg, "jpg"),
+    ("image/png", ImageFormat::Png, "png"),
+    ("image/webp", ImageFormat::WebP, "webp"),
+];
+
+// A file of a few KB can declare huge dimensions, those are refused before anything is allocated;
+const MAX_IMAGE_DIMENSION: u32 = 10_000;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ImageData {
+    id: i32,
+    url: String,
+    thumbnail_url: String,
+    content_type: String,
+    size: i64,
+    width: i32,
+    height: i32,
+    position: i32,
+    is_primary: bool,
+}
+
+impl ImageData {
+    pub fn new(image: product_image::Model, storage: &dyn Storage) -> Self {
+        Self {
+            id: image.id,
+            url: storage.url(&image.storage_key),
+            thumbnail_url: storage.url(&image.thumbnail_key),
+            content_type: image.content_type,
+            size: image.size,
+            width: image.width,
+            height: image.height,
+            position: image.position,
+            is_primary: image.is_primary,
+        }
+    }
+}
+
+// The upload's dimensions and its thumbnail;
+struct ProcessedImage {
+    width: u32,
+    height: u32,
+    thumbnail: Vec<u8>,
+    thumbnail_type: &'static str,
+    thumbnail_extension: &'static str,
+}
+
+pub struct ImageService;
+
+impl ImageService {
+    // Checked as soon as the part headers are read, before the file itself is;
+    pub fn check_content_type(content_type: &str) -> APIResult<()> {
+        accepted_type(content_type).map(|_| ())
+    }
+
+    // 1. The bytes must decode as the declared content type, the thumbnail is made on the rayon pool;
+    // 2. Both files are stored under a random name, so a replaced image is never served from a stale cache;
+    // 3. The product's first image becomes its primary one, later ones are appended after the last position;
+    pub async fn upload(
+        db: &DbConn,
+        storage: &dyn Storage,
+        product_id: i32,
+        content_type: &str,
+        bytes: Vec<u8>,
+        thumbnail_size: u32,
+    ) -> APIResult<product_image::Model> {
+        let (content_type, format, extension) = accepted_type(content_type)?;
+
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        let (tx, rx) = oneshot::channel();
+
+        rayon::spawn(move || {
+            let processed = process(&bytes, format, thumbnail_size);
+
+            let _ = tx.send((bytes, processed));
+        });
+
+        let (bytes, processed) = rx.await?;
+        let processed = processed?;
+
+        let name = format!("products/{}/{}", product_id, generate_random_string(16));
+        let storage_key = format!("{}.{}", name, extension);
+        let thumbnail_key = format!("{}_thumb.{}", name, processed.thumbnail_extension);
+        let size = bytes.len() as i64;
+
+        let stored: APIResult<product_image::Model> = async {
+            storage.put(&storage_key, content_type, bytes).await?;
+            storage
+                .put(
+                    &thumbnail_key,
+                    processed.thumbnail_type,
+                    processed.thumbnail,
+                )
+                .await?;
+
+            let image = product_image::ActiveModel {
+                product_id: Set(product_id),
+                storage_key: Set(storage_key.clone()),
+                thumbnail_key: Set(thumbnail_key.clone()),
+                content_type: Set(content_type.to_string()),
+                size: Set(size),
+                width: Set(processed.width as i32),
+                height: Set(processed.height as i32),
+                created_at: Set(Utc::now().into()),
+                ..Default::default()
+            };
+
+            // Concurrent first uploads all see no image yet, those losing the primary index are appended again;
+            let created = match append(db, product_id, image.clone(), true).await {
+                Err(e) if is_unique_violation(&e, PRODUCT_IMAGE_PRIMARY_INDEX) => {
+                    append(db, product_id, image, false).await?
+                }
+                created => created?,
+            };
+
+            Ok(created)
+        }
+        .await;
+
+        if stored.is_err() {
+            discard(storage, [&storage_key, &thumbnail_key]).await;
+        }
+
+        stored
+    }
+
+    pub async fn find(db: &DbConn, product_id: i32) -> APIResult<Vec<product_image::Model>> {
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        Ok(Self::find_for_products(db, [product_id])
+            .await?
+            .remove(&product_id)
+            .unwrap_or_default())
+    }
+
+    // The images of every listed product in one query, each product's in display order;
+    pub async fn find_for_products(
+        db: &impl ConnectionTrait,
+        product_ids: impl IntoIterator<Item = i32>,
+    ) -> APIResult<HashMap<i32, Vec<product_image::Model>>> {
+        let mut images: HashMap<i32, Vec<product_image::Model>> = HashMap::new();
+
+        for image in ProductImage::find()
+            .filter(product_image::Column::ProductId.is_in(product_ids))
+            .order_by_asc(product_image::Column::Position)
+            .order_by_asc(product_image::Column::Id)
+            .all(db)
+            .await?
+        {
+            images.entry(image.product_id).or_default().push(image);
+        }
+
+        Ok(images)
+    }
+
+    // `image_ids` is the new display order and must hold each of the product's images once;
+    pub async fn reorder(db: &DbConn, product_id: i32, image_ids: Vec<i32>) -> APIResult<()> {
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        let txn = db.begin().await?;
+
+        let mut current: Vec<i32> = ProductImage::find()
+            .filter(product_image::Column::ProductId.eq(product_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|image| image.id)
+            .collect();
+        let mut requested = image_ids.clone();
+        current.sort_unstable();
+        requested.sort_unstable();
+
+        if current != requested {
+            return Err(AppError::InvalidImageOrder);
+        }
+
+        for (position, id) in image_ids.into_iter().enumerate() {
+            product_image::ActiveModel {
+                id: Set(id),
+                position: Set(position as i32),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn set_primary(db: &DbConn, product_id: i32, image_id: i32) -> APIResult<()> {
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        let txn = db.begin().await?;
+
+        let image = find_image(&txn, product_id, image_id).await?;
+
+        // The previous primary is cleared first, the partial unique index allows a single one;
+        ProductImage::update_many()
+            .col_expr(product_image::Column::IsPrimary, false.into())
+            .filter(product_image::Column::ProductId.eq(product_id))
+            .filter(product_image::Column::IsPrimary.eq(true))
+            .exec(&txn)
+            .await?;
+
+        let mut image: product_image::ActiveModel = image.into();
+        image.is_primary = Set(true);
+        image.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    // Deleting the primary image hands the flag to the first remaining one;
+    // The files are removed once the row is gone, a failure there only leaves an unreferenced file behind;
+    pub async fn delete(
+        db: &DbConn,
+        storage: &dyn Storage,
+        product_id: i32,
+        image_id: i32,
+    ) -> APIResult<()> {
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        let txn = db.begin().await?;
+
+        let image = find_image(&txn, product_id, image_id).await?;
+        image.clone().delete(&txn).await?;
+
+        if image.is_primary {
+            let next = ProductImage::find()
+                .filter(product_image::Column::ProductId.eq(product_id))
+                .order_by_asc(product_image::Column::Position)
+                .order_by_asc(product_image::Column::Id)
+                .one(&txn)
+                .await?;
+
+            if let Some(next) = next {
+                let mut next: product_image::ActiveModel = next.into();
+                next.is_primary = Set(true);
+                next.update(&txn).await?;
+            }
+        }
+
+        txn.commit().await?;
+
+        discard(storage, [&image.storage_key, &image.thumbnail_key]).await;
+
+        Ok(())
+    }
+}
+
+// Inserts `image` after the product's last one, as its primary image if it is the first and `may_be_primary`;
+async fn append(
+    db: &DbConn,
+    product_id: i32,
+    mut image: product_image::ActiveModel,
+    may_be_primary: bool,
+) -> Result<product_image::Model, DbErr> {
+    let txn = db.begin().await?;
+
+    let last = ProductImage::find()
+        .filter(product_image::Column::ProductId.eq(product_id))
+        .order_by_desc(product_image::Column::Position)
+        .one(&txn)
+        .await?;
+
+    image.position = Set(last.as_ref().map_or(0, |image| image.position + 1));
+    image.is_primary = Set(may_be_primary && last.is_none());
+
+    let created = image.insert(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(created)
+}
+
+fn accepted_type(content_type: &str) -> APIResult<(&'static str, ImageFormat, &'static str)> {
+    ACCEPTED_TYPES
+        .into_iter()
+        .find(|(accepted, _, _)| accepted.eq_ignore_ascii_case(content_type))
+        .ok_or(AppError::UnsupportedImageType)
+}
+
+async fn find_image(
+    db: &impl ConnectionTrait,
+    product_id: i32,
+    image_id: i32,
+) -> APIResult<product_image::Model> {
+    ProductImage::find_by_id(image_id)
+        .filter(product_image::Column::ProductId.eq(product_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::ImageNotFound)
+}
+
+// Decodes the upload, whose dimensions are kept, and scales it down to fit a `size` square;
+// PNG thumbnails stay PNG to keep their transparency, anything else becomes a JPEG;
+fn process(bytes: &[u8], format: ImageFormat, size: u32) -> APIResult<ProcessedImage> {
+    if image::guess_format(bytes).ok() != Some(format) {
+        return Err(AppError::InvalidImage);
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|_| AppError::InvalidImage)?;
+
+    let scaled = image.thumbnail(size, size);
+    let mut encoded = Cursor::new(Vec::new());
+
+    let (thumbnail_type, thumbnail_extension) = if format == ImageFormat::Png {
+        scaled.write_to(&mut encoded, ImageOutputFormat::Png)?;
+        ("image/png", "png")
+    } else {
+        DynamicImage::ImageRgb8(scaled.to_rgb8())
+            .write_to(&mut encoded, ImageOutputFormat::Jpeg(85))?;
+        ("image/jpeg", "jpg")
+    };
+
+    Ok(ProcessedImage {
+        width: image.width(),
+        height: image.height(),
+        thumbnail: encoded.into_inner(),
+        thumbnail_type,
+        thumbnail_extension,
+    })
+}
+
+pub(super) async fn discard(storage: &dyn Storage, keys: [&str; 2]) {
+    for key in keys {
+        if let Err(e) = storage.delete(key).await {
+            tracing::warn!(key, error = %e, "Failed to delete stored image");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, ItemsAndPagesNumber,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+
+use ::entity::{
+    inventory_movement,
+    prelude::{InventoryMovement, Product},
+    sea_orm_active_enums::InventoryReason,
+};
+
+use super::{page_matcher, size_matcher, StockItem, StockLevels};
+use crate::errors::{APIResult, AppError};
+
+// Every change to a stock or reserved_stock counter is written to the append-only inventory_movement ledger;
+// For reservation entries the quantity is the change in reserved stock, for every other reason the change in stock;
+// Entries of a variant carry both the product and the variant id, stock_after being the variant's stock;
+pub struct InventoryService;
+
+impl InventoryService {
+    // Must run inside the same transaction as the stock change so stock_after reflects it;
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        item: StockItem,
+        user_id: i32,
+        reason: InventoryReason,
+        quantity: i32,
+        note: Option<String>,
+    ) -> APIResult<()> {
+        let stock_after = item.levels(db).await?.stock;
+
+        inventory_movement::ActiveModel {
+            product_id: Set(item.product_id),
+            variant_id: Set(item.variant_id),
+            user_id: Set(user_id),
+            reason: Set(reason),
+            quantity: Set(quantity),
+            stock_after: Set(stock_after),
+            note: Set(note),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(())
+    }
+
+    // Manual stock changes coming from the API, sales and reservations are only written by the checkout flow;
+    pub async fn adjust(
+        db: &DbConn,
+        product_id: i32,
+        variant_id: Option<i32>,
+        user_id: i32,
+        quantity: i32,
+        reason: InventoryReason,
+        note: Option<String>,
+    ) -> APIResult<StockLevels> {
+        if !matches!(
+            reason,
+            InventoryReason::Restock | InventoryReason::Return | InventoryReason::Adjustment
+        ) {
+            return Err(AppError::InvalidInventoryReason);
+        }
+
+        // Restocks and returns only add stock, a removal has to be booked as an adjustment;
+        if quantity == 0 || (quantity < 0 && reason != InventoryReason::Adjustment) {
+            return Err(AppError::InvalidQuantity);
+        }
+
+        let txn = db.begin().await?;
+
+        let current = if let Some(p) = Product::find_by_id(product_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        {
+            p
+        } else {
+            return Err(AppError::ProductNotFound);
+        };
+
+        if current.deleted_at.is_some() {
+            return Err(AppError::ProductAlreadyDeleted);
+        }
+
+        let item = StockItem::of(&txn, product_id, variant_id).await?;
+
+        // Stock that is currently held by reservations cannot be taken away;
+        if !item.adjust(&txn, quantity).await? {
+            return Err(AppError::InvalidStock);
+        }
+
+        Self::record(&txn, item, user_id, reason, quantity, note).await?;
+
+        let levels = item.levels(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(levels)
+    }
+
+    pub async fn history(
+        db: &DbConn,
+        product_id: i32,
+        page: Option<i32>,
+        size: Option<i32>,
+    ) -> APIResult<(Vec<inventory_movement::Model>, u64, u64)> {
+        if (Product::find_by_id(product_id).one(db).await?).is_none() {
+            return Err(AppError::ProductNotFound);
+        }
+
+        let page = page_matcher(page)?;
+        let size = size_matcher(size)?;
+
+        let ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages,
+        } = InventoryMovement::find()
+            .filter(inventory_movement::Column::ProductId.eq(product_id))
+            .paginate(db, size)
+            .num_items_and_pages()
+            .await?;
+        let data = InventoryMovement::find()
+            .filter(inventory_movement::Column::ProductId.eq(product_id))
+            .order_by_desc(inventory_movement::Column::Id)
+            .paginate(db, size)
+            .fetch_page(page)
+            .await?;
+
+        Ok((data, number_of_items, number_of_pages))
+    }
+}
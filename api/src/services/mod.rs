@@ -2,13 +2,36 @@ mod auth_service;
 mod brand_service;
 mod cart_service;
 mod category_service;
+mod health_service;
+mod image_service;
+mod inventory_service;
+mod order_service;
+mod pagination;
 mod product_service;
+mod reservation_service;
+mod search_service;
+mod soft_delete_service;
+mod stock_service;
 
 pub use auth_service::AuthService;
 pub use brand_service::BrandService;
 pub use cart_service::{CartData, CartService};
-pub use category_service::CategoryService;
-pub use product_service::{ProductData, ProductService};
+pub use category_service::{CategoryNode, CategoryService};
+pub use health_service::{HealthService, MigrationStatus};
+pub use image_service::{ImageData, ImageService};
+pub use inventory_service::InventoryService;
+pub use order_service::{OrderData, OrderItemData, OrderService, OrderSummary};
+pub use pagination::{paginate, KeyKind, Keyset, Page, PageRequest};
+pub use product_service::{
+    FacetCount, PriceBucket, ProductData, ProductFacets, ProductParent, ProductService, VariantData,
+};
+pub use reservation_service::ReservationService;
+pub use search_service::{ProductSearch, SearchHit};
+pub use soft_delete_service::SoftDeleteService;
+pub use stock_service::{StockItem, StockLevels};
+
+use migration::{Expr, Func, IntoColumnRef, LikeExpr, SimpleExpr};
+use sea_orm::DbErr;
 
 use crate::errors::{APIResult, AppError};
 
@@ -25,10 +48,13 @@ pub fn page_matcher(page: Option<i32>) -> APIResult<u64> {
     }
 }
 
+// Upper bound of every page size, offset or cursor based;
+pub const MAX_PAGE_SIZE: i32 = 100;
+
 pub fn size_matcher(size: Option<i32>) -> APIResult<u64> {
     match size {
         Some(s) => {
-            if s <= 0 {
+            if s <= 0 || s > MAX_PAGE_SIZE {
                 Err(AppError::InvalidSize)
             } else {
                 Ok((s) as u64)
@@ -37,3 +63,34 @@ pub fn size_matcher(size: Option<i32>) -> APIResult<u64> {
         None => Ok(10),
     }
 }
+
+// Case-insensitive substring match that behaves the same on Postgres (LIKE is case-sensitive there) and SQLite;
+// The keyword's own % and _ are escaped so they match literally, "!" is used since backslash quoting differs between backends;
+pub fn contains_ignore_case<C: IntoColumnRef>(column: C, keyword: &str) -> SimpleExpr {
+    let escaped = keyword
+        .to_lowercase()
+        .replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_");
+
+    Expr::expr(Func::lower(Expr::col(column)))
+        .like(LikeExpr::new(format!("%{}%", escaped)).escape('!'))
+}
+
+// Uniqueness is enforced by the unique indexes, a violation is turned into the error paired with that index;
+// Postgres and SQLite both name the index in the message, any other database error is passed through;
+pub fn unique_violation<const N: usize>(err: DbErr, violations: [(&str, AppError); N]) -> AppError {
+    for (index, violation) in violations {
+        if is_unique_violation(&err, index) {
+            return violation;
+        }
+    }
+
+    AppError::DBError(err)
+}
+
+pub fn is_unique_violation(err: &DbErr, index: &str) -> bool {
+    let message = err.to_string();
+
+    message.to_lowercase().contains("unique") && message.contains(index)
+}
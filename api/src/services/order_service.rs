@@ -0,0 +1,369 @@
+use chrono::Utc;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait,
+    DbConn, EntityTrait, IntoActiveModel, ItemsAndPagesNumber, ModelTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use ::entity::{
+    cart, order, order_item, order_status_history,
+    prelude::{Cart, Order, OrderItem, OrderStatusHistory, Product, ProductVariant},
+    product, product_variant,
+    sea_orm_active_enums::{InventoryReason, OrderStatus},
+};
+
+use super::{page_matcher, size_matcher, InventoryService, ReservationService, StockItem};
+use crate::errors::{APIResult, AppError};
+use crate::utils::money::Money;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderSummary {
+    pub id: i32,
+    user_id: i32,
+    status: OrderStatus,
+    total: Money,
+    #[schema(value_type = String, format = DateTime)]
+    created_at: DateTimeWithTimeZone,
+    #[schema(value_type = String, format = DateTime)]
+    updated_at: DateTimeWithTimeZone,
+}
+
+impl From<order::Model> for OrderSummary {
+    fn from(order: order::Model) -> Self {
+        Self {
+            id: order.id,
+            user_id: order.user_id,
+            status: order.status,
+            total: Money::new(order.total, order.currency),
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderItemData {
+    id: i32,
+    product_id: i32,
+    variant_id: Option<i32>,
+    product_name: String,
+    sku: Option<String>,
+    price: Money,
+    quantity: i32,
+    subtotal: Money,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderData {
+    #[serde(flatten)]
+    order: OrderSummary,
+    items: Vec<OrderItemData>,
+}
+
+pub struct OrderService;
+
+impl OrderService {
+    // Converts the user's cart into a pending order;
+    // Product name and price are snapshotted into the line items so later catalog edits won't alter past orders;
+    pub async fn checkout(db: &DbConn, user_id: i32) -> APIResult<OrderSummary> {
+        let txn = db.begin().await?;
+
+        let cart_items = Cart::find()
+            .filter(cart::Column::UserId.eq(user_id))
+            .all(&txn)
+            .await?;
+
+        if cart_items.is_empty() {
+            return Err(AppError::EmptyCart);
+        }
+
+        // Lock the purchased product rows in a stable order so concurrent checkouts cannot deadlock;
+        let products: HashMap<i32, product::Model> = Product::find()
+            .filter(product::Column::Id.is_in(cart_items.iter().map(|c| c.product_id)))
+            .order_by_asc(product::Column::Id)
+            .lock_exclusive()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+        let variants: HashMap<i32, product_variant::Model> = ProductVariant::find()
+            .filter(
+                product_variant::Column::Id.is_in(cart_items.iter().filter_map(|c| c.variant_id)),
+            )
+            .order_by_asc(product_variant::Column::Id)
+            .lock_exclusive()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|v| (v.id, v))
+            .collect();
+
+        let mut total: Option<Money> = None;
+        let mut items = Vec::with_capacity(cart_items.len());
+
+        for cart_item in cart_items {
+            let cart_product = match products.get(&cart_item.product_id) {
+                Some(p) if p.deleted_at.is_none() => p.clone(),
+                _ => return Err(AppError::ProductNotFound),
+            };
+
+            // A variant's price overrides the product's, in the product's currency;
+            let (variant, price) = match cart_item.variant_id {
+                Some(v) => {
+                    let variant = variants.get(&v).ok_or(AppError::VariantNotFound)?;
+                    (Some(variant), variant.price.unwrap_or(cart_product.price))
+                }
+                None => (None, cart_product.price),
+            };
+
+            let item = StockItem::new(cart_product.id, cart_item.variant_id);
+            ReservationService::consume(&txn, user_id, item, cart_item.quantity).await?;
+
+            // An order is settled in a single currency, mixing them fails in checked_add;
+            let price = Money::new(price, cart_product.currency);
+            let subtotal = price.checked_mul(cart_item.quantity)?;
+            total = Some(match total {
+                Some(t) => t.checked_add(&subtotal)?,
+                None => subtotal.clone(),
+            });
+
+            items.push(order_item::ActiveModel {
+                product_id: Set(cart_product.id),
+                variant_id: Set(cart_item.variant_id),
+                product_name: Set(cart_product.name),
+                sku: Set(variant.map(|v| v.sku.clone())),
+                price: Set(price.amount()),
+                quantity: Set(cart_item.quantity),
+                subtotal: Set(subtotal.amount()),
+                created_at: Set(Utc::now().into()),
+                ..Default::default()
+            });
+        }
+
+        let total = total.ok_or(AppError::EmptyCart)?;
+
+        let order = order::ActiveModel {
+            user_id: Set(user_id),
+            status: Set(OrderStatus::Pending),
+            total: Set(total.amount()),
+            currency: Set(total.currency().to_string()),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        for item in items.iter_mut() {
+            item.order_id = Set(order.id);
+        }
+
+        OrderItem::insert_many(items).exec(&txn).await?;
+
+        Self::record_transition(&txn, order.id, None, OrderStatus::Pending, user_id).await?;
+
+        Cart::delete_many()
+            .filter(cart::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(order.into())
+    }
+
+    pub async fn find(
+        db: &DbConn,
+        user_id: i32,
+        page: Option<i32>,
+        size: Option<i32>,
+    ) -> APIResult<(Vec<OrderSummary>, u64, u64)> {
+        let page = page_matcher(page)?;
+        let size = size_matcher(size)?;
+
+        let ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages,
+        } = Order::find()
+            .filter(order::Column::UserId.eq(user_id))
+            .paginate(db, size)
+            .num_items_and_pages()
+            .await?;
+        let data = Order::find()
+            .filter(order::Column::UserId.eq(user_id))
+            .order_by_desc(order::Column::CreatedAt)
+            .paginate(db, size)
+            .fetch_page(page)
+            .await?
+            .into_iter()
+            .map(OrderSummary::from)
+            .collect();
+
+        Ok((data, number_of_items, number_of_pages))
+    }
+
+    // owner_id restricts the lookup to orders of that user, None is for admins;
+    pub async fn get(db: &DbConn, id: i32, owner_id: Option<i32>) -> APIResult<OrderData> {
+        let mut query = Order::find_by_id(id);
+
+        if let Some(owner_id) = owner_id {
+            query = query.filter(order::Column::UserId.eq(owner_id));
+        }
+
+        let order = if let Some(o) = query.one(db).await? {
+            o
+        } else {
+            return Err(AppError::OrderNotFound);
+        };
+
+        let items = order
+            .find_related(OrderItem)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|item| OrderItemData {
+                id: item.id,
+                product_id: item.product_id,
+                variant_id: item.variant_id,
+                product_name: item.product_name,
+                sku: item.sku,
+                price: Money::new(item.price, order.currency.as_str()),
+                quantity: item.quantity,
+                subtotal: Money::new(item.subtotal, order.currency.as_str()),
+            })
+            .collect();
+
+        Ok(OrderData {
+            order: order.into(),
+            items,
+        })
+    }
+
+    // Moves an order to the next status, rejecting anything outside of the lifecycle:
+    // pending -> paid -> shipped -> delivered, with pending -> cancelled and paid/delivered -> refunded branches;
+    // Cancelling an unpaid order gives the reserved stock back within the same transaction;
+    pub async fn transition(
+        db: &DbConn,
+        id: i32,
+        to: OrderStatus,
+        actor_id: i32,
+        owner_id: Option<i32>,
+    ) -> APIResult<OrderSummary> {
+        let txn = db.begin().await?;
+
+        let mut query = Order::find_by_id(id).lock_exclusive();
+
+        if let Some(owner_id) = owner_id {
+            query = query.filter(order::Column::UserId.eq(owner_id));
+        }
+
+        let current = if let Some(o) = query.one(&txn).await? {
+            o
+        } else {
+            return Err(AppError::OrderNotFound);
+        };
+        let from = current.status;
+
+        if matches!(from, OrderStatus::Cancelled | OrderStatus::Refunded) {
+            return Err(AppError::OrderAlreadyClosed);
+        }
+
+        if !is_valid_transition(from, to) {
+            return Err(AppError::InvalidOrderTransition(
+                from.to_value(),
+                to.to_value(),
+            ));
+        }
+
+        if from == OrderStatus::Pending && to == OrderStatus::Cancelled {
+            let items = current.find_related(OrderItem).all(&txn).await?;
+
+            for item in items {
+                let stock_item = StockItem::new(item.product_id, item.variant_id);
+                stock_item.adjust(&txn, item.quantity).await?;
+
+                InventoryService::record(
+                    &txn,
+                    stock_item,
+                    actor_id,
+                    InventoryReason::Return,
+                    item.quantity,
+                    Some(format!("Order #{} cancelled", id)),
+                )
+                .await?;
+            }
+        }
+
+        let mut order = current.into_active_model();
+        order.status = Set(to);
+        order.updated_at = Set(Utc::now().into());
+        let order = order.update(&txn).await?;
+
+        Self::record_transition(&txn, order.id, Some(from), to, actor_id).await?;
+
+        txn.commit().await?;
+
+        Ok(order.into())
+    }
+
+    pub async fn history(
+        db: &DbConn,
+        id: i32,
+        owner_id: Option<i32>,
+    ) -> APIResult<Vec<order_status_history::Model>> {
+        let mut query = Order::find_by_id(id);
+
+        if let Some(owner_id) = owner_id {
+            query = query.filter(order::Column::UserId.eq(owner_id));
+        }
+
+        let order = if let Some(o) = query.one(db).await? {
+            o
+        } else {
+            return Err(AppError::OrderNotFound);
+        };
+
+        Ok(order
+            .find_related(OrderStatusHistory)
+            .order_by_asc(order_status_history::Column::CreatedAt)
+            .all(db)
+            .await?)
+    }
+
+    async fn record_transition<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        from: Option<OrderStatus>,
+        to: OrderStatus,
+        actor_id: i32,
+    ) -> APIResult<()> {
+        order_status_history::ActiveModel {
+            order_id: Set(order_id),
+            from_status: Set(from),
+            to_status: Set(to),
+            actor_id: Set(actor_id),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn is_valid_transition(from: OrderStatus, to: OrderStatus) -> bool {
+    matches!(
+        (from, to),
+        (OrderStatus::Pending, OrderStatus::Paid)
+            | (OrderStatus::Pending, OrderStatus::Cancelled)
+            | (OrderStatus::Paid, OrderStatus::Shipped)
+            | (OrderStatus::Paid, OrderStatus::Refunded)
+            | (OrderStatus::Shipped, OrderStatus::Delivered)
+            | (OrderStatus::Delivered, OrderStatus::Refunded)
+    )
+}
@@ -0,0 +1,299 @@
+use migration::{Condition, Expr, Order, SimpleExpr};
+use sea_orm::{
+    ConnectionTrait, DbConn, EntityTrait, FromQueryResult, IntoSimpleExpr, ItemsAndPagesNumber,
+    PaginatorTrait, QueryFilter, QueryOrder, QueryResult, QuerySelect, QueryTrait, Select,
+};
+
+use super::{page_matcher, size_matcher};
+use crate::errors::{APIResult, AppError};
+use crate::utils::cursor::{Cursor, CursorValue, Direction};
+
+// How a list endpoint was asked to page: by page number, or from a cursor of a previous response;
+// A cursor takes precedence over the page number;
+#[derive(Debug)]
+pub struct PageRequest {
+    size: u64,
+    offset: u64,
+    cursor: Option<Cursor>,
+    count: bool,
+}
+
+impl PageRequest {
+    // The total count costs a second query, `count=false` skips it;
+    pub fn new(
+        page: Option<i32>,
+        size: Option<i32>,
+        cursor: Option<String>,
+        count: Option<bool>,
+    ) -> APIResult<Self> {
+        let size = size_matcher(size)?;
+        let offset = page_matcher(page)? * size;
+        let cursor = cursor.as_deref().map(Cursor::decode).transpose()?;
+
+        Ok(Self {
+            size,
+            offset,
+            cursor,
+            count: count.unwrap_or(true),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub total_items: Option<u64>,
+    pub total_page: Option<u64>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn try_map<U>(self, f: impl FnMut(T) -> APIResult<U>) -> APIResult<Page<U>> {
+        Ok(Page {
+            data: self.data.into_iter().map(f).collect::<APIResult<_>>()?,
+            total_items: self.total_items,
+            total_page: self.total_page,
+            next_cursor: self.next_cursor,
+            prev_cursor: self.prev_cursor,
+        })
+    }
+}
+
+// Type of a key column's values, as they are read back to build the cursors;
+#[derive(Debug, Clone, Copy)]
+pub enum KeyKind {
+    Int,
+    BigInt,
+    Float,
+    Text,
+    Time,
+}
+
+impl KeyKind {
+    fn read(self, row: &QueryResult, column: &str) -> APIResult<CursorValue> {
+        Ok(match self {
+            KeyKind::Int => CursorValue::Int(row.try_get("", column)?),
+            KeyKind::BigInt => CursorValue::BigInt(row.try_get("", column)?),
+            KeyKind::Float => CursorValue::Float(row.try_get("", column)?),
+            KeyKind::Text => CursorValue::Text(row.try_get("", column)?),
+            KeyKind::Time => CursorValue::Time(row.try_get("", column)?),
+        })
+    }
+
+    fn accepts(self, value: &CursorValue) -> bool {
+        matches!(
+            (self, value),
+            (KeyKind::Int, CursorValue::Int(_))
+                | (KeyKind::BigInt, CursorValue::BigInt(_))
+                | (KeyKind::Float, CursorValue::Float(_))
+                | (KeyKind::Text, CursorValue::Text(_))
+                | (KeyKind::Time, CursorValue::Time(_))
+        )
+    }
+}
+
+#[derive(Debug)]
+struct KeyColumn {
+    expr: SimpleExpr,
+    descending: bool,
+    kind: KeyKind,
+}
+
+// The order a listing is paged by; the columns must not be NULL and, together, must be unique,
+// so every listing ends with its id;
+// The scope names the listing and its order, cursors of another listing are refused;
+#[derive(Debug)]
+pub struct Keyset {
+    scope: String,
+    columns: Vec<KeyColumn>,
+}
+
+impl Keyset {
+    pub fn new(scope: impl Into<String>) -> Self {
+        Self {
+            scope: scope.into(),
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn asc(self, expr: impl IntoSimpleExpr, kind: KeyKind) -> Self {
+        self.column(expr, false, kind)
+    }
+
+    pub fn desc(self, expr: impl IntoSimpleExpr, kind: KeyKind) -> Self {
+        self.column(expr, true, kind)
+    }
+
+    fn column(mut self, expr: impl IntoSimpleExpr, descending: bool, kind: KeyKind) -> Self {
+        self.columns.push(KeyColumn {
+            expr: expr.into_simple_expr(),
+            descending,
+            kind,
+        });
+
+        self
+    }
+
+    fn accepts(&self, cursor: &Cursor) -> bool {
+        cursor.scope == self.scope
+            && cursor.key.len() == self.columns.len()
+            && self
+                .columns
+                .iter()
+                .zip(&cursor.key)
+                .all(|(column, value)| column.kind.accepts(value))
+    }
+
+    // Whether a column is read towards lower values, going in that direction;
+    fn falling(column: &KeyColumn, direction: Direction) -> bool {
+        column.descending == (direction == Direction::After)
+    }
+
+    // Rows strictly past the key in the direction, compared column by column:
+    // (a > x) OR (a = x AND b > y) OR ..., which unlike a row comparison allows mixed orders;
+    fn condition(&self, key: Vec<CursorValue>, direction: Direction) -> Condition {
+        let mut past = Condition::any();
+
+        for (i, column) in self.columns.iter().enumerate() {
+            let mut tied = Condition::all();
+
+            for (previous, value) in self.columns[..i].iter().zip(&key) {
+                tied = tied.add(Expr::expr(previous.expr.clone()).eq(value.clone()));
+            }
+
+            let value = key[i].clone();
+            let expr = Expr::expr(column.expr.clone());
+
+            past = past.add(tied.add(if Self::falling(column, direction) {
+                expr.lt(value)
+            } else {
+                expr.gt(value)
+            }));
+        }
+
+        past
+    }
+
+    fn key(&self, row: &QueryResult) -> APIResult<Vec<CursorValue>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| column.kind.read(row, &key_alias(i)))
+            .collect()
+    }
+
+    fn cursor(&self, row: &QueryResult, direction: Direction) -> APIResult<String> {
+        Ok(Cursor {
+            scope: self.scope.clone(),
+            direction,
+            key: self.key(row)?,
+        }
+        .encode())
+    }
+}
+
+fn key_alias(i: usize) -> String {
+    format!("cursor_{}", i)
+}
+
+// Runs a listing ordered by its keyset, one page of `M`;
+// Without a cursor the page is found by offset, with one by the key of the row it points at, which stays
+// fast and stable however far the listing goes;
+// A page backwards is read in reverse order and flipped back, so both directions return rows in listing order;
+pub async fn paginate<E, M>(
+    db: &DbConn,
+    select: Select<E>,
+    keyset: Keyset,
+    request: PageRequest,
+) -> APIResult<Page<M>>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    M: FromQueryResult,
+{
+    let (total_items, total_page) = if request.count {
+        let ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages,
+        } = select
+            .clone()
+            .paginate(db, request.size)
+            .num_items_and_pages()
+            .await?;
+
+        (Some(number_of_items), Some(number_of_pages))
+    } else {
+        (None, None)
+    };
+
+    let direction = request
+        .cursor
+        .as_ref()
+        .map_or(Direction::After, |cursor| cursor.direction);
+    let from_cursor = request.cursor.is_some();
+
+    let mut select = match request.cursor {
+        Some(cursor) => {
+            if !keyset.accepts(&cursor) {
+                return Err(AppError::InvalidCursor);
+            }
+
+            select.filter(keyset.condition(cursor.key, cursor.direction))
+        }
+        None => select.offset(request.offset),
+    };
+
+    for (i, column) in keyset.columns.iter().enumerate() {
+        let order = if Keyset::falling(column, direction) {
+            Order::Desc
+        } else {
+            Order::Asc
+        };
+
+        select = select
+            .column_as(column.expr.clone(), key_alias(i).as_str())
+            .order_by(column.expr.clone(), order);
+    }
+
+    // One extra row tells whether there is more past this page;
+    let statement = select
+        .limit(request.size + 1)
+        .build(db.get_database_backend());
+    let mut rows = db.query_all(statement).await?;
+
+    let more = rows.len() as u64 > request.size;
+    rows.truncate(request.size as usize);
+
+    if direction == Direction::Before {
+        rows.reverse();
+    }
+
+    let (has_next, has_prev) = match (from_cursor, direction) {
+        (false, _) => (more, request.offset > 0),
+        (true, Direction::After) => (more, true),
+        (true, Direction::Before) => (true, more),
+    };
+
+    let next_cursor = match rows.last() {
+        Some(row) if has_next => Some(keyset.cursor(row, Direction::After)?),
+        _ => None,
+    };
+    let prev_cursor = match rows.first() {
+        Some(row) if has_prev => Some(keyset.cursor(row, Direction::Before)?),
+        _ => None,
+    };
+
+    let data = rows
+        .iter()
+        .map(|row| M::from_query_result(row, ""))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Page {
+        data,
+        total_items,
+        total_page,
+        next_cursor,
+        prev_cursor,
+    })
+}
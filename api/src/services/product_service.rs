@@ -1,34 +1,514 @@
 use chrono::Utc;
-use migration::{Condition, Expr, Func, IntoCondition, JoinType};
+use migration::{
+    CaseStatement, Condition, Expr, Func, IntoCondition, JoinType, Query, SimpleExpr,
+    SubQueryStatement, PRODUCT_NAME_INDEX, PRODUCT_VARIANT_SKU_INDEX,
+};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, FromQueryResult, IntoActiveModel,
-    ItemsAndPagesNumber, PaginatorTrait, QueryFilter, QuerySelect, RelationTrait, Set,
+    prelude::{DateTimeWithTimeZone, Json},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, FromQueryResult,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select,
+    Set, TransactionTrait,
 };
 use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
 
 use ::entity::{
     brand, category,
-    prelude::{Brand, Category, Product},
-    product,
+    prelude::{Brand, Category, Product, ProductVariant},
+    product, product_variant,
+    sea_orm_active_enums::InventoryReason,
 };
 
-use super::{page_matcher, size_matcher};
+use super::{
+    contains_ignore_case, paginate, unique_violation, CategoryService, ImageData, ImageService,
+    InventoryService, KeyKind, Keyset, Page, PageRequest, ProductSearch, SearchHit,
+    SoftDeleteService, StockItem,
+};
 use crate::{
+    config::CascadePolicy,
     errors::{APIResult, AppError},
-    handler::product::UpdateProductData,
+    handler::product::{
+        CreateProductData, CreateVariantData, FindProductParams, ProductIdFilters, ProductSort,
+        UpdateProductData,
+    },
+    storage::Storage,
+    utils::money::{Money, DEFAULT_CURRENCY},
 };
 
-#[derive(Serialize, Debug, FromQueryResult)]
+#[derive(Debug, FromQueryResult)]
+struct ProductRow {
+    id: i32,
+    brand_id: i32,
+    category_id: i32,
+    name: String,
+    description: Option<String>,
+    price: i64,
+    currency: String,
+    stock: i32,
+    reserved_stock: i32,
+    available_stock: i32,
+    brand_name: Option<String>,
+    category_name: Option<String>,
+    search_rank: Option<f64>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct VariantData {
+    id: i32,
+    sku: String,
+    #[schema(value_type = Object, example = json!({"size": "M", "color": "red"}))]
+    options: Json,
+    price: Money,
+    stock: i32,
+    reserved_stock: i32,
+    available_stock: i32,
+}
+
+impl VariantData {
+    // Variants without a price of their own are sold at the product's price;
+    fn new(variant: product_variant::Model, product_price: &Money) -> Self {
+        Self {
+            id: variant.id,
+            sku: variant.sku,
+            options: variant.options,
+            price: Money::new(
+                variant.price.unwrap_or_else(|| product_price.amount()),
+                product_price.currency(),
+            ),
+            stock: variant.stock,
+            reserved_stock: variant.reserved_stock,
+            available_stock: variant.stock - variant.reserved_stock,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
 pub struct ProductData {
     id: i32,
     brand_id: i32,
     category_id: i32,
     name: String,
     description: Option<String>,
-    price: i32,
+    price: Money,
     stock: i32,
-    brand_name: String,
-    category_name: String,
+    reserved_stock: i32,
+    available_stock: i32,
+    brand_name: Option<String>,
+    category_name: Option<String>,
+    variants: Vec<VariantData>,
+    // In display order, the primary image is flagged;
+    images: Vec<ImageData>,
+    // Only when searching;
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<SearchHit>,
+}
+
+impl From<ProductRow> for ProductData {
+    fn from(row: ProductRow) -> Self {
+        Self {
+            id: row.id,
+            brand_id: row.brand_id,
+            category_id: row.category_id,
+            name: row.name,
+            description: row.description,
+            price: Money::new(row.price, row.currency),
+            stock: row.stock,
+            reserved_stock: row.reserved_stock,
+            available_stock: row.available_stock,
+            brand_name: row.brand_name,
+            category_name: row.category_name,
+            variants: Vec::new(),
+            images: Vec::new(),
+            search: None,
+        }
+    }
+}
+
+// The two entities a product belongs to, whose deletion is cascaded according to a CascadePolicy;
+#[derive(Debug, Clone, Copy)]
+pub enum ProductParent {
+    Brand,
+    Category,
+}
+
+impl ProductParent {
+    fn column(self) -> product::Column {
+        match self {
+            ProductParent::Brand => product::Column::BrandId,
+            ProductParent::Category => product::Column::CategoryId,
+        }
+    }
+
+    fn in_use(self) -> AppError {
+        match self {
+            ProductParent::Brand => AppError::BrandInUse,
+            ProductParent::Category => AppError::CategoryInUse,
+        }
+    }
+
+    // Products whose other parent is still active, so restoring one parent does not bring back
+    // a product that stays hidden behind the other one;
+    fn other_parent_active(self) -> Condition {
+        let (column, parents) = match self {
+            ProductParent::Brand => (
+                product::Column::CategoryId,
+                Query::select()
+                    .column(category::Column::Id)
+                    .from(Category)
+                    .and_where(category::Column::DeletedAt.is_null())
+                    .to_owned(),
+            ),
+            ProductParent::Category => (
+                product::Column::BrandId,
+                Query::select()
+                    .column(brand::Column::Id)
+                    .from(Brand)
+                    .and_where(brand::Column::DeletedAt.is_null())
+                    .to_owned(),
+            ),
+        };
+
+        Condition::all().add(column.in_subquery(parents))
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema, FromQueryResult)]
+pub struct FacetCount {
+    id: i32,
+    // Absent once the brand or category is deleted, its products may still be listed (detach policy or `all`);
+    name: Option<String>,
+    count: i64,
+}
+
+// Prices from `min` (included) up to `max` (excluded), no `max` for the last bucket;
+#[derive(Serialize, Debug, ToSchema)]
+pub struct PriceBucket {
+    min: i64,
+    max: Option<i64>,
+    count: i64,
+}
+
+// Result counts per brand, category and price bucket, most common brands and categories first;
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ProductFacets {
+    brands: Vec<FacetCount>,
+    categories: Vec<FacetCount>,
+    // The currency of the price buckets, only its products are counted there;
+    currency: String,
+    prices: Vec<PriceBucket>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BucketCount {
+    bucket: i32,
+    count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facet {
+    Brand,
+    Category,
+    Price,
+}
+
+// The filters of a product listing;
+// Each facet is counted under every filter but its own, so with a brand picked the other brands
+// still show how many results they would give;
+struct ProductFilter {
+    keyword: Option<String>,
+    search: Option<ProductSearch>,
+    brand_ids: Vec<i32>,
+    // The requested categories and all of their subcategories;
+    category_ids: Vec<i32>,
+    // Restricts the listing when asked for, else only the price filters and buckets;
+    currency: Option<String>,
+    // The currency of min_price, max_price and the price buckets, whose prices are in its minor units;
+    price_currency: String,
+    min_price: Option<i64>,
+    max_price: Option<i64>,
+    in_stock: Option<bool>,
+    all: bool,
+}
+
+impl ProductFilter {
+    async fn new(db: &DbConn, params: FindProductParams, ids: ProductIdFilters) -> APIResult<Self> {
+        let FindProductParams {
+            keyword,
+            search,
+            all,
+            currency,
+            min_price,
+            max_price,
+            in_stock,
+            ..
+        } = params;
+
+        if matches!(min_price, Some(p) if p < 0)
+            || matches!(max_price, Some(p) if p < 0)
+            || matches!((min_price, max_price), (Some(min), Some(max)) if min > max)
+        {
+            return Err(AppError::InvalidPriceRange);
+        }
+
+        let search = search
+            .map(|s| ProductSearch::parse(&s, db.get_database_backend()))
+            .transpose()?;
+
+        let currency = currency.as_deref().map(Money::parse_currency).transpose()?;
+        let price_currency = currency
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+
+        let ProductIdFilters {
+            brand_ids,
+            category_ids,
+        } = ids;
+
+        let subtrees = CategoryService::subtree_ids(db, &category_ids).await?;
+
+        Ok(Self {
+            keyword,
+            search,
+            brand_ids,
+            category_ids: subtrees,
+            currency,
+            price_currency,
+            min_price,
+            max_price,
+            in_stock,
+            all: all.is_some(),
+        })
+    }
+
+    fn condition(&self, except: Option<Facet>) -> Condition {
+        let mut condition = Condition::all();
+
+        if let Some(k) = &self.keyword {
+            condition = condition.add(contains_ignore_case(
+                product::Column::Name.as_column_ref(),
+                k,
+            ));
+        }
+
+        if let Some(s) = &self.search {
+            condition = condition.add(s.condition());
+        }
+
+        if !self.brand_ids.is_empty() && except != Some(Facet::Brand) {
+            condition = condition.add(product::Column::BrandId.is_in(self.brand_ids.clone()));
+        }
+
+        if !self.category_ids.is_empty() && except != Some(Facet::Category) {
+            condition = condition.add(product::Column::CategoryId.is_in(self.category_ids.clone()));
+        }
+
+        if let Some(c) = &self.currency {
+            condition = condition.add(product::Column::Currency.eq(c.as_str()));
+        }
+
+        // Prices of other currencies are not comparable, bounds only keep products of the price currency;
+        if except != Some(Facet::Price) && (self.min_price.is_some() || self.max_price.is_some()) {
+            condition = condition.add(product::Column::Currency.eq(self.price_currency.as_str()));
+
+            if let Some(p) = self.min_price {
+                condition = condition.add(Expr::expr(effective_price()).gte(p));
+            }
+
+            if let Some(p) = self.max_price {
+                condition = condition.add(Expr::expr(effective_price()).lte(p));
+            }
+        }
+
+        match self.in_stock {
+            Some(true) => condition = condition.add(in_stock()),
+            Some(false) => condition = condition.add(in_stock().not()),
+            None => {}
+        }
+
+        if !self.all {
+            condition = condition.add(product::Column::DeletedAt.is_null());
+        }
+
+        condition
+    }
+
+    async fn facets(&self, db: &DbConn, price_bounds: &[i64]) -> APIResult<ProductFacets> {
+        let mut brands = joined(
+            Product::find()
+                .select_only()
+                .column_as(product::Column::BrandId, "id")
+                .column_as(brand::Column::Name, "name")
+                .column_as(
+                    Expr::tbl(product::Entity, product::Column::Id).count(),
+                    "count",
+                )
+                .filter(self.condition(Some(Facet::Brand))),
+        )
+        .group_by(product::Column::BrandId)
+        .group_by(brand::Column::Name)
+        .into_model::<FacetCount>()
+        .all(db)
+        .await?;
+
+        let mut categories = joined(
+            Product::find()
+                .select_only()
+                .column_as(product::Column::CategoryId, "id")
+                .column_as(category::Column::Name, "name")
+                .column_as(
+                    Expr::tbl(product::Entity, product::Column::Id).count(),
+                    "count",
+                )
+                .filter(self.condition(Some(Facet::Category))),
+        )
+        .group_by(product::Column::CategoryId)
+        .group_by(category::Column::Name)
+        .into_model::<FacetCount>()
+        .all(db)
+        .await?;
+
+        for counts in [&mut brands, &mut categories] {
+            counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.id.cmp(&b.id)));
+        }
+
+        // Bucket i holds the prices below bound i and not below the previous one;
+        let bucket: SimpleExpr = price_bounds
+            .iter()
+            .enumerate()
+            .fold(CaseStatement::new(), |case, (i, &bound)| {
+                case.case(
+                    Expr::expr(effective_price()).lt(bound),
+                    SimpleExpr::Value((i as i32).into()),
+                )
+            })
+            .finally(SimpleExpr::Value((price_bounds.len() as i32).into()))
+            .into();
+
+        // Grouped by the output name, Postgres would not match the bucket expression's own parameters;
+        let counts: HashMap<i32, i64> = joined(
+            Product::find()
+                .select_only()
+                .column_as(bucket, "bucket")
+                .column_as(
+                    Expr::tbl(product::Entity, product::Column::Id).count(),
+                    "count",
+                )
+                .filter(self.condition(Some(Facet::Price)))
+                .filter(product::Column::Currency.eq(self.price_currency.as_str())),
+        )
+        .group_by(Expr::cust(r#""bucket""#))
+        .into_model::<BucketCount>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| (b.bucket, b.count))
+        .collect();
+
+        let prices = (0..=price_bounds.len())
+            .map(|i| PriceBucket {
+                min: if i == 0 { 0 } else { price_bounds[i - 1] },
+                max: price_bounds.get(i).copied(),
+                count: counts.get(&(i as i32)).copied().unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(ProductFacets {
+            brands,
+            categories,
+            currency: self.price_currency.clone(),
+            prices,
+        })
+    }
+}
+
+// Listings join the product's active brand and category, whose names are shown, searched and counted;
+fn joined(select: Select<Product>) -> Select<Product> {
+    select
+        .join(
+            JoinType::LeftJoin,
+            product::Relation::Category
+                .def()
+                .on_condition(|_left_t, right_t| {
+                    Expr::tbl(right_t, category::Column::DeletedAt)
+                        .is_null()
+                        .into_condition()
+                }),
+        )
+        .join(
+            JoinType::LeftJoin,
+            product::Relation::Brand
+                .def()
+                .on_condition(|_left_t, right_t| {
+                    Expr::tbl(right_t, brand::Column::DeletedAt)
+                        .is_null()
+                        .into_condition()
+                }),
+        )
+}
+
+fn available_stock() -> SimpleExpr {
+    Expr::tbl(product::Entity, product::Column::Stock)
+        .into_simple_expr()
+        .sub(Expr::tbl(product::Entity, product::Column::ReservedStock))
+}
+
+// The lowest price a product sells at: its cheapest variant's, a variant without a price of its own
+// selling at the product's, or the product's price when it has no variants;
+// Price filters, buckets and sorts all go by it;
+fn effective_price() -> SimpleExpr {
+    let cheapest_variant = Query::select()
+        .expr(Func::min(Func::coalesce([
+            Expr::tbl(product_variant::Entity, product_variant::Column::Price).into_simple_expr(),
+            Expr::tbl(product::Entity, product::Column::Price).into_simple_expr(),
+        ])))
+        .from(ProductVariant)
+        .and_where(
+            Expr::tbl(product_variant::Entity, product_variant::Column::ProductId)
+                .equals(product::Entity, product::Column::Id),
+        )
+        .to_owned();
+
+    Func::coalesce([
+        SimpleExpr::SubQuery(
+            None,
+            Box::new(SubQueryStatement::SelectStatement(cheapest_variant)),
+        ),
+        Expr::tbl(product::Entity, product::Column::Price).into_simple_expr(),
+    ])
+}
+
+// Products that can be bought now: one of their variants has unreserved stock,
+// or, for a product without variants, the product itself has;
+fn in_stock() -> Condition {
+    let variants = Query::select()
+        .column(product_variant::Column::ProductId)
+        .from(ProductVariant)
+        .to_owned();
+
+    let variants_in_stock = Query::select()
+        .column(product_variant::Column::ProductId)
+        .from(ProductVariant)
+        .and_where(
+            Expr::expr(
+                Expr::tbl(product_variant::Entity, product_variant::Column::Stock)
+                    .into_simple_expr()
+                    .sub(Expr::tbl(
+                        product_variant::Entity,
+                        product_variant::Column::ReservedStock,
+                    )),
+            )
+            .gt(0),
+        )
+        .to_owned();
+
+    Condition::any()
+        .add(product::Column::Id.in_subquery(variants_in_stock))
+        .add(
+            Condition::all()
+                .add(product::Column::Id.not_in_subquery(variants))
+                .add(Expr::expr(available_stock()).gt(0)),
+        )
 }
 
 pub struct ProductService;
@@ -36,13 +516,21 @@ pub struct ProductService;
 impl ProductService {
     pub async fn create(
         db: &DbConn,
-        name: String,
-        price: i32,
-        stock: i32,
-        category_id: i32,
-        brand_id: i32,
-        description: Option<String>,
+        create_data: CreateProductData,
+        user_id: i32,
     ) -> APIResult<product::Model> {
+        let CreateProductData {
+            name,
+            price,
+            currency,
+            stock,
+            category_id,
+            brand_id,
+            description,
+        } = create_data;
+
+        let currency = Money::parse_currency(currency.as_deref().unwrap_or(DEFAULT_CURRENCY))?;
+
         if stock < 1 {
             return Err(AppError::InvalidStock);
         }
@@ -51,37 +539,17 @@ impl ProductService {
             return Err(AppError::InvalidPrice);
         }
 
-        if (Category::find_by_id(category_id)
-            .filter(category::Column::DeletedAt.is_null())
-            .one(db)
-            .await?)
-            .is_none()
-        {
-            return Err(AppError::CategoryNotFound);
-        }
+        SoftDeleteService::find_active::<Category>(db, category_id).await?;
+        SoftDeleteService::find_active::<Brand>(db, brand_id).await?;
 
-        if (Brand::find_by_id(brand_id)
-            .filter(brand::Column::DeletedAt.is_null())
-            .one(db)
-            .await?)
-            .is_none()
-        {
-            return Err(AppError::BrandNotFound);
-        }
-
-        if (Product::find()
-            .filter(product::Column::Name.eq(name.as_str()))
-            .one(db)
-            .await?)
-            .is_some()
-        {
-            return Err(AppError::ProductAlreadyCreated);
-        }
+        let txn = db.begin().await?;
 
-        Ok(product::ActiveModel {
+        let created = product::ActiveModel {
             name: Set(name),
             price: Set(price),
+            currency: Set(currency),
             stock: Set(stock),
+            reserved_stock: Set(0),
             category_id: Set(category_id),
             brand_id: Set(brand_id),
             description: Set(description),
@@ -89,152 +557,173 @@ impl ProductService {
             updated_at: Set(Utc::now().into()),
             ..Default::default()
         }
-        .insert(db)
-        .await?)
+        .insert(&txn)
+        .await
+        .map_err(|e| {
+            unique_violation(e, [(PRODUCT_NAME_INDEX, AppError::ProductAlreadyCreated)])
+        })?;
+
+        InventoryService::record(
+            &txn,
+            StockItem::new(created.id, None),
+            user_id,
+            InventoryReason::Restock,
+            stock,
+            Some("Initial stock".to_string()),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(created)
     }
 
+    // A search filters on name, description, brand and category and orders the results by relevance;
     pub async fn find(
         db: &DbConn,
-        keyword: Option<String>,
-        page: Option<i32>,
-        size: Option<i32>,
-        all: Option<bool>,
-    ) -> APIResult<(Vec<ProductData>, u64, u64)> {
-        let mut condition = Condition::all();
-
-        if let Some(l) = keyword {
-            let like = format!("%{}%", l.to_lowercase());
+        storage: &dyn Storage,
+        mut params: FindProductParams,
+        ids: ProductIdFilters,
+        price_facet_bounds: &[i64],
+    ) -> APIResult<(Page<ProductData>, ProductFacets)> {
+        let request =
+            PageRequest::new(params.page, params.size, params.cursor.take(), params.count)?;
+        let sort = params.sort;
+
+        let filter = ProductFilter::new(db, params, ids).await?;
+
+        let rank = filter.search.as_ref().map(ProductSearch::rank);
+
+        let query = joined(
+            Product::find()
+                .select_only()
+                .columns([
+                    product::Column::Id,
+                    product::Column::BrandId,
+                    product::Column::CategoryId,
+                    product::Column::Name,
+                    product::Column::Description,
+                    product::Column::Price,
+                    product::Column::Currency,
+                    product::Column::Stock,
+                    product::Column::ReservedStock,
+                ])
+                .column_as(available_stock(), "available_stock")
+                .column_as(category::Column::Name, "category_name")
+                .column_as(brand::Column::Name, "brand_name")
+                .column_as(
+                    rank.clone().unwrap_or_else(|| Expr::cust("NULL")),
+                    "search_rank",
+                )
+                .filter(filter.condition(None)),
+        );
+
+        let keyset = match (sort, rank) {
+            // Grouped by currency, prices are only compared within one;
+            (Some(ProductSort::PriceAsc), _) => Keyset::new("products:price_asc")
+                .asc(product::Column::Currency, KeyKind::Text)
+                .asc(effective_price(), KeyKind::BigInt)
+                .asc(product::Column::Id, KeyKind::Int),
+            (Some(ProductSort::PriceDesc), _) => Keyset::new("products:price_desc")
+                .asc(product::Column::Currency, KeyKind::Text)
+                .desc(effective_price(), KeyKind::BigInt)
+                .asc(product::Column::Id, KeyKind::Int),
+            (Some(ProductSort::Newest), _) => Keyset::new("products:newest")
+                .desc(product::Column::CreatedAt, KeyKind::Time)
+                .desc(product::Column::Id, KeyKind::Int),
+            // Lowercased, SQLite would otherwise put every capitalized name first;
+            (Some(ProductSort::Name), _) => Keyset::new("products:name")
+                .asc(
+                    Func::lower(Expr::tbl(product::Entity, product::Column::Name)),
+                    KeyKind::Text,
+                )
+                .asc(product::Column::Id, KeyKind::Int),
+            (None, Some(r)) => Keyset::new("products:relevance")
+                .desc(r, KeyKind::Float)
+                .asc(product::Column::Id, KeyKind::Int),
+            (None, None) => Keyset::new("products").asc(product::Column::Id, KeyKind::Int),
+        };
 
-            condition = condition.add(
-                Expr::expr(Func::lower(Expr::col(
-                    product::Column::Name.as_column_ref(),
-                )))
-                .like(like),
-            );
+        let mut page = paginate::<_, ProductRow>(db, query, keyset, request)
+            .await?
+            .try_map(|row| {
+                let hit = filter.search.as_ref().map(|s| {
+                    s.hit(
+                        row.search_rank.unwrap_or_default(),
+                        &row.name,
+                        row.description.as_deref(),
+                    )
+                });
+
+                Ok(ProductData {
+                    search: hit,
+                    ..row.into()
+                })
+            })?;
+        let data = &mut page.data;
+
+        let mut variants: HashMap<i32, Vec<product_variant::Model>> = HashMap::new();
+
+        for variant in ProductVariant::find()
+            .filter(product_variant::Column::ProductId.is_in(data.iter().map(|p| p.id)))
+            .order_by_asc(product_variant::Column::Id)
+            .all(db)
+            .await?
+        {
+            variants
+                .entry(variant.product_id)
+                .or_default()
+                .push(variant);
         }
 
-        if all.is_none() {
-            condition = condition.add(product::Column::DeletedAt.is_null());
+        let mut images = ImageService::find_for_products(db, data.iter().map(|p| p.id)).await?;
+
+        for product in data.iter_mut() {
+            product.variants = variants
+                .remove(&product.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| VariantData::new(v, &product.price))
+                .collect();
+            product.images = images
+                .remove(&product.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|i| ImageData::new(i, storage))
+                .collect();
         }
 
-        let size = size_matcher(size)?;
-        let page = page_matcher(page)?;
-
-        let ItemsAndPagesNumber {
-            number_of_items,
-            number_of_pages,
-        } = Product::find()
-            .filter(condition.clone())
-            .join(
-                JoinType::LeftJoin,
-                product::Relation::Category
-                    .def()
-                    .on_condition(|_left_t, right_t| {
-                        Expr::tbl(right_t, category::Column::DeletedAt)
-                            .is_null()
-                            .into_condition()
-                    }),
-            )
-            .join(
-                JoinType::LeftJoin,
-                product::Relation::Brand
-                    .def()
-                    .on_condition(|_left_t, right_t| {
-                        Expr::tbl(right_t, brand::Column::DeletedAt)
-                            .is_null()
-                            .into_condition()
-                    }),
-            )
-            .paginate(db, size)
-            .num_items_and_pages()
-            .await?;
-
-        let data = Product::find()
-            .select_only()
-            .columns([
-                product::Column::Id,
-                product::Column::BrandId,
-                product::Column::CategoryId,
-                product::Column::Name,
-                product::Column::Description,
-                product::Column::Price,
-                product::Column::Stock,
-            ])
-            .column_as(category::Column::Name, "category_name")
-            .column_as(brand::Column::Name, "brand_name")
-            .filter(condition)
-            .join(
-                JoinType::LeftJoin,
-                product::Relation::Category
-                    .def()
-                    .on_condition(|_left_t, right_t| {
-                        Expr::tbl(right_t, category::Column::DeletedAt)
-                            .is_null()
-                            .into_condition()
-                    }),
-            )
-            .join(
-                JoinType::LeftJoin,
-                product::Relation::Brand
-                    .def()
-                    .on_condition(|_left_t, right_t| {
-                        Expr::tbl(right_t, brand::Column::DeletedAt)
-                            .is_null()
-                            .into_condition()
-                    }),
-            )
-            .into_model::<ProductData>()
-            .paginate(db, size)
-            .fetch_page(page)
-            .await?;
+        let facets = filter.facets(db, price_facet_bounds).await?;
 
-        Ok((data, number_of_items, number_of_pages))
+        Ok((page, facets))
     }
 
     pub async fn update(db: &DbConn, id: i32, update_data: UpdateProductData) -> APIResult<()> {
         let UpdateProductData {
             name,
             price,
-            stock,
+            currency,
             description,
             category_id,
             brand_id,
         } = update_data;
 
-        let mut product = if let Some(p) = Product::find_by_id(id).one(db).await? {
-            if p.deleted_at.is_some() {
-                return Err(AppError::ProductAlreadyDeleted);
-            } else {
-                p.into_active_model()
-            }
-        } else {
-            return Err(AppError::ProductNotFound);
-        };
+        let product = SoftDeleteService::find_with_deleted::<Product>(db, id).await?;
+
+        if product.deleted_at.is_some() {
+            return Err(AppError::ProductAlreadyDeleted);
+        }
+
+        let mut product = product.into_active_model();
 
         if let Some(c) = category_id {
-            if (Category::find_by_id(c)
-                .filter(brand::Column::DeletedAt.is_null())
-                .one(db)
-                .await?)
-                .is_none()
-            {
-                return Err(AppError::CategoryNotFound);
-            } else {
-                product.category_id = Set(c);
-            }
+            SoftDeleteService::find_active::<Category>(db, c).await?;
+            product.category_id = Set(c);
         }
 
         if let Some(b) = brand_id {
-            if (Brand::find_by_id(b)
-                .filter(brand::Column::DeletedAt.is_null())
-                .one(db)
-                .await?)
-                .is_none()
-            {
-                return Err(AppError::BrandNotFound);
-            } else {
-                product.brand_id = Set(b);
-            }
+            SoftDeleteService::find_active::<Brand>(db, b).await?;
+            product.brand_id = Set(b);
         }
 
         if let Some(n) = name {
@@ -249,12 +738,15 @@ impl ProductService {
             }
         }
 
-        if let Some(s) = stock {
-            if s < 1 {
-                return Err(AppError::InvalidStock);
-            } else {
-                product.stock = Set(s);
+        // The price is kept in minor units, a new currency alone would silently rescale it;
+        if let Some(c) = currency {
+            let c = Money::parse_currency(&c)?;
+
+            if price.is_none() && product.currency.as_ref() != &c {
+                return Err(AppError::CurrencyChangeWithoutPrice);
             }
+
+            product.currency = Set(c);
         }
 
         if description.is_some() {
@@ -262,41 +754,136 @@ impl ProductService {
         }
 
         product.updated_at = Set(Utc::now().into());
-        product.update(db).await?;
+        product.update(db).await.map_err(|e| {
+            unique_violation(e, [(PRODUCT_NAME_INDEX, AppError::ProductAlreadyCreated)])
+        })?;
 
         Ok(())
     }
 
-    pub async fn delete(db: &DbConn, id: i32) -> APIResult<()> {
-        let mut product = if let Some(p) = Product::find_by_id(id).one(db).await? {
-            if p.deleted_at.is_some() {
-                return Err(AppError::ProductAlreadyDeleted);
-            } else {
-                p.into_active_model()
+    pub async fn create_variant(
+        db: &DbConn,
+        product_id: i32,
+        create_data: CreateVariantData,
+        user_id: i32,
+    ) -> APIResult<product_variant::Model> {
+        let CreateVariantData {
+            sku,
+            options,
+            price,
+            stock,
+        } = create_data;
+
+        if stock < 0 {
+            return Err(AppError::InvalidStock);
+        }
+
+        if matches!(price, Some(p) if p < 1) {
+            return Err(AppError::InvalidPrice);
+        }
+
+        SoftDeleteService::find_active::<Product>(db, product_id).await?;
+
+        let txn = db.begin().await?;
+
+        let created = product_variant::ActiveModel {
+            product_id: Set(product_id),
+            sku: Set(sku),
+            options: Set(Json::Object(
+                options
+                    .into_iter()
+                    .map(|(k, v)| (k, Json::String(v)))
+                    .collect(),
+            )),
+            price: Set(price),
+            stock: Set(stock),
+            reserved_stock: Set(0),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| unique_violation(e, [(PRODUCT_VARIANT_SKU_INDEX, AppError::DuplicateSku)]))?;
+
+        if stock > 0 {
+            InventoryService::record(
+                &txn,
+                StockItem::new(product_id, Some(created.id)),
+                user_id,
+                InventoryReason::Restock,
+                stock,
+                Some("Initial stock".to_string()),
+            )
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(created)
+    }
+
+    // Applies the policy to the active products of a parent that was just soft-deleted at `deleted_at`,
+    // meant to run in the same transaction as the parent's delete;
+    pub async fn parent_deleted(
+        db: &impl ConnectionTrait,
+        parent: ProductParent,
+        parent_id: i32,
+        deleted_at: DateTimeWithTimeZone,
+        policy: CascadePolicy,
+    ) -> APIResult<()> {
+        let active = Condition::all()
+            .add(parent.column().eq(parent_id))
+            .add(product::Column::DeletedAt.is_null());
+
+        match policy {
+            CascadePolicy::Restrict => {
+                if Product::find().filter(active).count(db).await? > 0 {
+                    return Err(parent.in_use());
+                }
             }
-        } else {
-            return Err(AppError::ProductNotFound);
-        };
+            CascadePolicy::Cascade => {
+                Product::update_many()
+                    .col_expr(product::Column::DeletedAt, Expr::value(deleted_at))
+                    .filter(active)
+                    .exec(db)
+                    .await?;
+            }
+            CascadePolicy::Detach => {}
+        }
 
-        product.deleted_at = Set(Some(Utc::now().into()));
-        product.update(db).await?;
+        Ok(())
+    }
+
+    // Reverse of a cascade: the products deleted at the very moment their parent was are restored with it;
+    pub async fn parent_restored(
+        db: &impl ConnectionTrait,
+        parent: ProductParent,
+        parent_id: i32,
+        deleted_at: DateTimeWithTimeZone,
+    ) -> APIResult<()> {
+        Product::update_many()
+            .col_expr(
+                product::Column::DeletedAt,
+                Expr::value(Option::<DateTimeWithTimeZone>::None),
+            )
+            .filter(parent.column().eq(parent_id))
+            .filter(product::Column::DeletedAt.eq(deleted_at))
+            .filter(parent.other_parent_active())
+            .exec(db)
+            .await?;
 
         Ok(())
     }
 
-    pub async fn restore(db: &DbConn, id: i32) -> APIResult<()> {
-        let mut product = if let Some(p) = Product::find_by_id(id).one(db).await? {
-            if p.deleted_at.is_none() {
-                return Err(AppError::CannotRestoreProduct);
-            } else {
-                p.into_active_model()
-            }
-        } else {
-            return Err(AppError::ProductNotFound);
-        };
+    pub async fn delete(db: &DbConn, id: i32) -> APIResult<()> {
+        SoftDeleteService::soft_delete::<Product>(db, id).await?;
 
-        product.deleted_at = Set(None);
-        product.update(db).await?;
+        Ok(())
+    }
+
+    pub async fn restore(db: &DbConn, id: i32) -> APIResult<()> {
+        SoftDeleteService::restore::<Product>(db, id).await?;
 
         Ok(())
     }
@@ -0,0 +1,159 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, IntoActiveModel,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use std::time::Duration as StdDuration;
+use tokio::task::JoinHandle;
+
+use ::entity::{
+    prelude::StockReservation, sea_orm_active_enums::InventoryReason, stock_reservation,
+};
+
+use super::{InventoryService, StockItem};
+use crate::errors::{APIResult, AppError};
+
+// Stock held by a reservation is counted in the item's reserved_stock until the reservation row is removed,
+// whoever deletes a reservation row (update, checkout or the sweeper) is the one giving the quantity back;
+pub struct ReservationService;
+
+impl ReservationService {
+    // Sets the user's reservation for an item to exactly `quantity`, refreshing its expiry;
+    pub async fn reserve<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item: StockItem,
+        quantity: i32,
+        ttl: Duration,
+    ) -> APIResult<()> {
+        let existing = Self::find_locked(db, user_id, item).await?;
+        let held = existing.as_ref().map_or(0, |r| r.quantity);
+        let delta = quantity - held;
+
+        if delta > 0 {
+            if !item.reserve(db, delta).await? {
+                return Err(AppError::InsufficientStock);
+            }
+        } else if delta < 0 {
+            item.release(db, -delta).await?;
+        }
+
+        if delta != 0 {
+            InventoryService::record(db, item, user_id, InventoryReason::Reservation, delta, None)
+                .await?;
+        }
+
+        let expires_at = Utc::now() + ttl;
+
+        if let Some(existing) = existing {
+            let mut reservation = existing.into_active_model();
+            reservation.quantity = Set(quantity);
+            reservation.expires_at = Set(expires_at.into());
+            reservation.update(db).await?;
+        } else {
+            stock_reservation::ActiveModel {
+                user_id: Set(user_id),
+                product_id: Set(item.product_id),
+                variant_id: Set(item.variant_id),
+                quantity: Set(quantity),
+                expires_at: Set(expires_at.into()),
+                created_at: Set(Utc::now().into()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    // Turns the user's reservation into a sale: stock goes down by `quantity` and the held amount is released;
+    // Works whether or not the reservation is still around, falling back to the unreserved stock;
+    pub async fn consume<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item: StockItem,
+        quantity: i32,
+    ) -> APIResult<()> {
+        let existing = Self::find_locked(db, user_id, item).await?;
+        let held = existing.as_ref().map_or(0, |r| r.quantity);
+
+        if !item.consume(db, quantity, held).await? {
+            return Err(AppError::InsufficientStock);
+        }
+
+        if let Some(existing) = existing {
+            existing.delete(db).await?;
+
+            InventoryService::record(db, item, user_id, InventoryReason::Reservation, -held, None)
+                .await?;
+        }
+
+        InventoryService::record(db, item, user_id, InventoryReason::Sale, -quantity, None).await?;
+
+        Ok(())
+    }
+
+    // Background task releasing expired reservations once a minute;
+    pub fn spawn_sweeper(db: DbConn) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(StdDuration::from_secs(60));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = Self::release_expired(&db).await {
+                    tracing::error!(error = %e, "Failed to release expired stock reservations");
+                }
+            }
+        })
+    }
+
+    // Gives back the stock of every expired reservation, returns how many were released;
+    pub async fn release_expired(db: &DbConn) -> APIResult<usize> {
+        let txn = db.begin().await?;
+
+        let expired = StockReservation::find()
+            .filter(stock_reservation::Column::ExpiresAt.lte(Utc::now()))
+            .order_by_asc(stock_reservation::Column::ProductId)
+            .lock_exclusive()
+            .all(&txn)
+            .await?;
+        let released = expired.len();
+
+        for reservation in expired {
+            let item = StockItem::new(reservation.product_id, reservation.variant_id);
+            item.release(&txn, reservation.quantity).await?;
+
+            InventoryService::record(
+                &txn,
+                item,
+                reservation.user_id,
+                InventoryReason::Reservation,
+                -reservation.quantity,
+                Some("Reservation expired".to_string()),
+            )
+            .await?;
+
+            reservation.delete(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(released)
+    }
+
+    async fn find_locked<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item: StockItem,
+    ) -> APIResult<Option<stock_reservation::Model>> {
+        Ok(StockReservation::find()
+            .filter(stock_reservation::Column::UserId.eq(user_id))
+            .filter(stock_reservation::Column::ProductId.eq(item.product_id))
+            .filter(item.variant_condition(stock_reservation::Column::VariantId))
+            .lock_exclusive()
+            .one(db)
+            .await?)
+    }
+}
@@ -0,0 +1,248 @@
+use migration::{Condition, Expr, SimpleExpr};
+use sea_orm::DbBackend;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::errors::{APIResult, AppError};
+
+// Each term adds a clause to the query, the rest of a longer search is ignored;
+const MAX_TERMS: usize = 8;
+
+// Words of the description shown in a snippet, a few of them before the first match;
+const SNIPPET_WORDS: usize = 24;
+const SNIPPET_LEAD: usize = 6;
+
+// Postgres' default ts_rank weights for A (name), B (description) and C (brand and category),
+// reused by the SQLite fallback so both backends order results alike;
+const NAME_WEIGHT: f64 = 1.0;
+const DESCRIPTION_WEIGHT: f64 = 0.4;
+const PARENT_WEIGHT: f64 = 0.2;
+
+// A term matches the product, its brand or its category, through the GIN indexes of the search migration;
+const POSTGRES_TERM: &str = r#"("product"."search_vector" @@ to_tsquery('simple', $1) OR "product"."brand_id" IN (SELECT "id" FROM "brand" WHERE "deleted_at" IS NULL AND to_tsvector('simple', "name") @@ to_tsquery('simple', $1)) OR "product"."category_id" IN (SELECT "id" FROM "category" WHERE "deleted_at" IS NULL AND to_tsvector('simple', "name") @@ to_tsquery('simple', $1)))"#;
+
+// Names close enough to the whole search, by pg_trgm's word similarity threshold (0.6 by default);
+const POSTGRES_TYPO: &str = r#"$1 <% "product"."name""#;
+
+// The fields the SQLite fallback searches, with their weight;
+const SQLITE_FIELDS: [(&str, f64); 4] = [
+    (r#"lower("product"."name")"#, NAME_WEIGHT),
+    (
+        r#"lower(coalesce("product"."description", ''))"#,
+        DESCRIPTION_WEIGHT,
+    ),
+    (r#"lower(coalesce("brand"."name", ''))"#, PARENT_WEIGHT),
+    (r#"lower(coalesce("category"."name", ''))"#, PARENT_WEIGHT),
+];
+
+const POSTGRES_RANK: &str = r#"CAST(ts_rank("product"."search_vector" || setweight(to_tsvector('simple', coalesce("brand"."name", '')), 'C') || setweight(to_tsvector('simple', coalesce("category"."name", '')), 'C'), to_tsquery('simple', $1)) + word_similarity($2, "product"."name") AS double precision)"#;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SearchHit {
+    // Higher is more relevant, only comparable between results of the same search;
+    rank: f64,
+    // HTML-escaped, with the words starting with a search term wrapped in <mark>;
+    name: String,
+    // Excerpt of the description around its first match, absent when only other fields matched;
+    snippet: Option<String>,
+}
+
+// A parsed product search: every term must prefix a word of the product's name, description, brand or category;
+// Postgres also accepts names that are a close trigram match of the whole search, to forgive typos;
+#[derive(Debug, Clone)]
+pub struct ProductSearch {
+    terms: Vec<String>,
+    backend: DbBackend,
+}
+
+impl ProductSearch {
+    // Terms are the lowercased words of the input, anything else than letters and digits separates them,
+    // which also keeps tsquery operators out of the query;
+    pub fn parse(input: &str, backend: DbBackend) -> APIResult<Self> {
+        let mut terms: Vec<String> = Vec::new();
+
+        for (start, end) in words(input) {
+            let term = input[start..end].to_lowercase();
+
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+
+        terms.truncate(MAX_TERMS);
+
+        if terms.is_empty() {
+            return Err(AppError::InvalidSearch);
+        }
+
+        Ok(Self { terms, backend })
+    }
+
+    // Meant for a product query joined with its active brand and category;
+    pub fn condition(&self) -> Condition {
+        match self.backend {
+            DbBackend::Postgres => {
+                let all_terms = self.terms.iter().fold(Condition::all(), |all, term| {
+                    all.add(Expr::cust_with_values(
+                        POSTGRES_TERM,
+                        [format!("{}:*", term)],
+                    ))
+                });
+
+                Condition::any()
+                    .add(all_terms)
+                    .add(Expr::cust_with_values(POSTGRES_TYPO, [self.text()]))
+            }
+            _ => self.terms.iter().fold(Condition::all(), |all, term| {
+                all.add(
+                    SQLITE_FIELDS
+                        .iter()
+                        .fold(Condition::any(), |any, (field, _)| {
+                            any.add(Expr::cust_with_values(
+                                &sqlite_word_start(field),
+                                sqlite_patterns(term),
+                            ))
+                        }),
+                )
+            }),
+        }
+    }
+
+    // Relevance as a double, on the same joined query as the condition;
+    pub fn rank(&self) -> SimpleExpr {
+        match self.backend {
+            DbBackend::Postgres => {
+                let query = self
+                    .terms
+                    .iter()
+                    .map(|term| format!("{}:*", term))
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+
+                Expr::cust_with_values(POSTGRES_RANK, [query, self.text()])
+            }
+            _ => {
+                // A GLOB is 0 or 1 in SQLite, each matching field adds its weight per term;
+                let per_term = SQLITE_FIELDS
+                    .iter()
+                    .map(|(field, weight)| format!("{} * {}", sqlite_word_start(field), weight))
+                    .collect::<Vec<_>>()
+                    .join(" + ");
+                let sql = format!(
+                    "CAST(({}) AS REAL)",
+                    vec![per_term; self.terms.len()].join(" + ")
+                );
+                let patterns = self.terms.iter().flat_map(|term| {
+                    SQLITE_FIELDS
+                        .iter()
+                        .flat_map(move |_| sqlite_patterns(term))
+                });
+
+                Expr::cust_with_values(&sql, patterns)
+            }
+        }
+    }
+
+    pub fn hit(&self, rank: f64, name: &str, description: Option<&str>) -> SearchHit {
+        SearchHit {
+            rank,
+            name: self.highlight(name),
+            snippet: description.and_then(|d| self.snippet(d)),
+        }
+    }
+
+    fn text(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    fn matches(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+
+        self.terms
+            .iter()
+            .any(|term| word.starts_with(term.as_str()))
+    }
+
+    fn highlight(&self, text: &str) -> String {
+        let mut highlighted = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for (start, end) in words(text) {
+            highlighted.push_str(&escape_html(&text[last..start]));
+
+            let word = escape_html(&text[start..end]);
+            if self.matches(&text[start..end]) {
+                highlighted.push_str(&format!("<mark>{}</mark>", word));
+            } else {
+                highlighted.push_str(&word);
+            }
+
+            last = end;
+        }
+
+        highlighted.push_str(&escape_html(&text[last..]));
+
+        highlighted
+    }
+
+    fn snippet(&self, text: &str) -> Option<String> {
+        let words = words(text);
+        let first = words
+            .iter()
+            .position(|&(start, end)| self.matches(&text[start..end]))?;
+
+        let from = first.saturating_sub(SNIPPET_LEAD);
+        let to = (from + SNIPPET_WORDS).min(words.len());
+
+        let mut snippet = self.highlight(&text[words[from].0..words[to - 1].1]);
+        if from > 0 {
+            snippet.insert_str(0, "… ");
+        }
+        if to < words.len() {
+            snippet.push_str(" …");
+        }
+
+        Some(snippet)
+    }
+}
+
+// Byte ranges of the runs of letters and digits;
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(s) = start {
+        words.push((s, text.len()));
+    }
+
+    words
+}
+
+// The term starts the field or follows anything but an ASCII letter or digit, SQLite's lower() only folds ASCII anyway;
+// Terms are letters and digits only, there is nothing to escape in the GLOB patterns;
+fn sqlite_word_start(field: &str) -> String {
+    format!("({field} GLOB ? OR {field} GLOB ?)", field = field)
+}
+
+fn sqlite_patterns(term: &str) -> [String; 2] {
+    [format!("{}*", term), format!("*[^a-z0-9]{}*", term)]
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
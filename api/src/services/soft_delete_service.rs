@@ -0,0 +1,301 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, DbErr,
+    EntityTrait, IntoActiveModel, ModelTrait, PrimaryKeyTrait, QueryFilter, Set, TransactionTrait,
+    Value,
+};
+use std::{sync::Arc, time::Duration as StdDuration};
+use tokio::task::JoinHandle;
+
+use ::entity::{
+    brand, cart, category, inventory_movement, inventory_movement_archive,
+    prelude::{
+        Cart, InventoryMovement, InventoryMovementArchive, Product, ProductImage, ProductVariant,
+        StockReservation,
+    },
+    product, product_image, product_variant,
+    soft_delete::SoftDelete,
+    stock_reservation,
+};
+
+use super::image_service::discard;
+use crate::{
+    errors::{APIResult, AppError},
+    storage::Storage,
+};
+
+type Id<E> = <<E as EntityTrait>::PrimaryKey as PrimaryKeyTrait>::ValueType;
+
+// The errors each soft-deletable entity reports, so the generic operations keep the entity's own codes;
+pub trait SoftDeleteResource: SoftDelete {
+    fn not_found() -> AppError;
+    fn already_deleted() -> AppError;
+    fn not_deleted() -> AppError;
+}
+
+impl SoftDeleteResource for brand::Entity {
+    fn not_found() -> AppError {
+        AppError::BrandNotFound
+    }
+
+    fn already_deleted() -> AppError {
+        AppError::BrandAlreadyDeleted
+    }
+
+    fn not_deleted() -> AppError {
+        AppError::CannotRestoreBrand
+    }
+}
+
+impl SoftDeleteResource for category::Entity {
+    fn not_found() -> AppError {
+        AppError::CategoryNotFound
+    }
+
+    fn already_deleted() -> AppError {
+        AppError::CategoryAlreadyDeleted
+    }
+
+    fn not_deleted() -> AppError {
+        AppError::CannotRestoreCategory
+    }
+}
+
+impl SoftDeleteResource for product::Entity {
+    fn not_found() -> AppError {
+        AppError::ProductNotFound
+    }
+
+    fn already_deleted() -> AppError {
+        AppError::ProductAlreadyDeleted
+    }
+
+    fn not_deleted() -> AppError {
+        AppError::CannotRestoreProduct
+    }
+}
+
+pub struct SoftDeleteService;
+
+impl SoftDeleteService {
+    // A deleted row is reported as not found, like a row that never existed;
+    pub async fn find_active<E>(db: &impl ConnectionTrait, id: Id<E>) -> APIResult<E::Model>
+    where
+        E: SoftDeleteResource,
+    {
+        E::find_by_id(id)
+            .filter(E::deleted_at_column().is_null())
+            .one(db)
+            .await?
+            .ok_or_else(E::not_found)
+    }
+
+    pub async fn find_with_deleted<E>(db: &impl ConnectionTrait, id: Id<E>) -> APIResult<E::Model>
+    where
+        E: SoftDeleteResource,
+    {
+        E::find_by_id(id).one(db).await?.ok_or_else(E::not_found)
+    }
+
+    pub async fn soft_delete<E>(db: &impl ConnectionTrait, id: Id<E>) -> APIResult<E::Model>
+    where
+        E: SoftDeleteResource,
+        E::Model: IntoActiveModel<<E as SoftDelete>::ActiveModel>,
+    {
+        let model = Self::find_with_deleted::<E>(db, id).await?;
+
+        if E::is_deleted(&model) {
+            return Err(E::already_deleted());
+        }
+
+        Self::set_deleted_at::<E>(db, model, Some(Utc::now().into())).await
+    }
+
+    pub async fn restore<E>(db: &impl ConnectionTrait, id: Id<E>) -> APIResult<E::Model>
+    where
+        E: SoftDeleteResource,
+        E::Model: IntoActiveModel<<E as SoftDelete>::ActiveModel>,
+    {
+        let model = Self::find_with_deleted::<E>(db, id).await?;
+
+        if !E::is_deleted(&model) {
+            return Err(E::not_deleted());
+        }
+
+        Self::set_deleted_at::<E>(db, model, None).await
+    }
+
+    // Removes for good the rows deleted more than `retention` ago, returns how many were removed;
+    // A row still referenced elsewhere (e.g. a product in past orders) is kept and retried on the next run;
+    pub async fn purge<E>(db: &DbConn, retention: Duration) -> APIResult<u64>
+    where
+        E: SoftDeleteResource,
+        E::Model: IntoActiveModel<<E as SoftDelete>::ActiveModel>,
+    {
+        let expired = E::find_deleted()
+            .filter(E::deleted_at_column().lt(Utc::now() - retention))
+            .all(db)
+            .await?;
+
+        let mut purged = 0;
+
+        for model in expired {
+            let active: <E as SoftDelete>::ActiveModel = model.into_active_model();
+
+            match active.delete(db).await {
+                Ok(result) => purged += result.rows_affected,
+                Err(e) if is_foreign_key_violation(&e) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(purged)
+    }
+
+    // Like purge, but a product takes its images, variants, reservations and cart lines along and moves its
+    // stock ledger to the archive, all in one transaction, the stored image files are removed once it is committed;
+    // A product still in past orders is kept and retried on the next run;
+    pub async fn purge_products(
+        db: &DbConn,
+        storage: &dyn Storage,
+        retention: Duration,
+    ) -> APIResult<u64> {
+        let expired = Product::find_deleted()
+            .filter(product::Column::DeletedAt.lt(Utc::now() - retention))
+            .all(db)
+            .await?;
+
+        let mut purged = 0;
+
+        for model in expired {
+            let txn = db.begin().await?;
+
+            let images = ProductImage::find()
+                .filter(product_image::Column::ProductId.eq(model.id))
+                .all(&txn)
+                .await?;
+
+            ProductImage::delete_many()
+                .filter(product_image::Column::ProductId.eq(model.id))
+                .exec(&txn)
+                .await?;
+            StockReservation::delete_many()
+                .filter(stock_reservation::Column::ProductId.eq(model.id))
+                .exec(&txn)
+                .await?;
+            Cart::delete_many()
+                .filter(cart::Column::ProductId.eq(model.id))
+                .exec(&txn)
+                .await?;
+            Self::archive_ledger(&txn, model.id).await?;
+            ProductVariant::delete_many()
+                .filter(product_variant::Column::ProductId.eq(model.id))
+                .exec(&txn)
+                .await?;
+
+            // Dropping the transaction rolls the dependents back along with the product;
+            match model.delete(&txn).await {
+                Ok(result) => purged += result.rows_affected,
+                Err(e) if is_foreign_key_violation(&e) => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            txn.commit().await?;
+
+            for image in images {
+                discard(storage, [&image.storage_key, &image.thumbnail_key]).await;
+            }
+        }
+
+        Ok(purged)
+    }
+
+    // Background task purging the soft-deleted catalog once an hour;
+    // Products go first since they reference brands and categories;
+    pub fn spawn_purger(
+        db: DbConn,
+        storage: Arc<dyn Storage>,
+        retention: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(StdDuration::from_secs(60 * 60));
+
+            loop {
+                interval.tick().await;
+
+                let purged = async {
+                    Ok::<_, AppError>(
+                        Self::purge_products(&db, storage.as_ref(), retention).await?
+                            + Self::purge::<brand::Entity>(&db, retention).await?
+                            + Self::purge::<category::Entity>(&db, retention).await?,
+                    )
+                };
+
+                if let Err(e) = purged.await {
+                    tracing::error!(error = %e, "Failed to purge soft-deleted rows");
+                }
+            }
+        })
+    }
+
+    // Copies the product's ledger rows to the archive before dropping them, so no stock change goes unexplained;
+    async fn archive_ledger(db: &impl ConnectionTrait, product_id: i32) -> APIResult<()> {
+        let movements = InventoryMovement::find()
+            .filter(inventory_movement::Column::ProductId.eq(product_id))
+            .all(db)
+            .await?;
+
+        if movements.is_empty() {
+            return Ok(());
+        }
+
+        let archived_at: DateTimeWithTimeZone = Utc::now().into();
+
+        InventoryMovementArchive::insert_many(movements.into_iter().map(|m| {
+            inventory_movement_archive::ActiveModel {
+                id: Set(m.id),
+                product_id: Set(m.product_id),
+                variant_id: Set(m.variant_id),
+                user_id: Set(m.user_id),
+                reason: Set(m.reason),
+                quantity: Set(m.quantity),
+                stock_after: Set(m.stock_after),
+                note: Set(m.note),
+                created_at: Set(m.created_at),
+                archived_at: Set(archived_at),
+            }
+        }))
+        .exec(db)
+        .await?;
+
+        InventoryMovement::delete_many()
+            .filter(inventory_movement::Column::ProductId.eq(product_id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_deleted_at<E>(
+        db: &impl ConnectionTrait,
+        model: E::Model,
+        deleted_at: Option<DateTimeWithTimeZone>,
+    ) -> APIResult<E::Model>
+    where
+        E: SoftDeleteResource,
+        E::Model: IntoActiveModel<<E as SoftDelete>::ActiveModel>,
+    {
+        let mut active: <E as SoftDelete>::ActiveModel = model.into_active_model();
+        active.set(
+            E::deleted_at_column(),
+            Value::ChronoDateTimeWithTimeZone(deleted_at.map(Box::new)),
+        );
+
+        Ok(active.update(db).await?)
+    }
+}
+
+// Postgres and SQLite word it differently but both mention the foreign key;
+fn is_foreign_key_violation(err: &DbErr) -> bool {
+    err.to_string().to_lowercase().contains("foreign key")
+}
@@ -0,0 +1,249 @@
+use chrono::Utc;
+use migration::{Expr, SimpleExpr};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QuerySelect,
+};
+
+use ::entity::{
+    prelude::{Product, ProductVariant},
+    product, product_variant,
+};
+
+use crate::errors::{APIResult, AppError};
+
+// Entities holding a stock counter and the part of it held by reservations;
+trait Stocked: EntityTrait {
+    fn id_column() -> Self::Column;
+    fn stock_column() -> Self::Column;
+    fn reserved_stock_column() -> Self::Column;
+    fn updated_at_column() -> Self::Column;
+}
+
+impl Stocked for Product {
+    fn id_column() -> Self::Column {
+        product::Column::Id
+    }
+
+    fn stock_column() -> Self::Column {
+        product::Column::Stock
+    }
+
+    fn reserved_stock_column() -> Self::Column {
+        product::Column::ReservedStock
+    }
+
+    fn updated_at_column() -> Self::Column {
+        product::Column::UpdatedAt
+    }
+}
+
+impl Stocked for ProductVariant {
+    fn id_column() -> Self::Column {
+        product_variant::Column::Id
+    }
+
+    fn stock_column() -> Self::Column {
+        product_variant::Column::Stock
+    }
+
+    fn reserved_stock_column() -> Self::Column {
+        product_variant::Column::ReservedStock
+    }
+
+    fn updated_at_column() -> Self::Column {
+        product_variant::Column::UpdatedAt
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct StockLevels {
+    pub stock: i32,
+    pub reserved_stock: i32,
+}
+
+// What a cart line, a reservation or a ledger entry holds stock of: the product itself,
+// or one of its variants, whose own stock is used instead of the product's;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockItem {
+    pub product_id: i32,
+    pub variant_id: Option<i32>,
+}
+
+impl StockItem {
+    pub fn new(product_id: i32, variant_id: Option<i32>) -> Self {
+        Self {
+            product_id,
+            variant_id,
+        }
+    }
+
+    // Checks the variant belongs to the product, a product with variants can only be sold through one of them;
+    // The product itself is expected to be checked by the caller;
+    pub async fn of(
+        db: &impl ConnectionTrait,
+        product_id: i32,
+        variant_id: Option<i32>,
+    ) -> APIResult<Self> {
+        let variants =
+            ProductVariant::find().filter(product_variant::Column::ProductId.eq(product_id));
+
+        if let Some(v) = variant_id {
+            variants
+                .filter(product_variant::Column::Id.eq(v))
+                .one(db)
+                .await?
+                .ok_or(AppError::VariantNotFound)?;
+        } else if variants.count(db).await? > 0 {
+            return Err(AppError::VariantRequired);
+        }
+
+        Ok(Self::new(product_id, variant_id))
+    }
+
+    // Matches the rows of this item in a table with a nullable variant_id column;
+    pub fn variant_condition(self, column: impl ColumnTrait) -> SimpleExpr {
+        match self.variant_id {
+            Some(v) => column.eq(v),
+            None => column.is_null(),
+        }
+    }
+
+    pub async fn levels(self, db: &impl ConnectionTrait) -> APIResult<StockLevels> {
+        let levels = match self.variant_id {
+            Some(v) => levels::<ProductVariant>(db, v).await?,
+            None => levels::<Product>(db, self.product_id).await?,
+        };
+
+        levels.ok_or(match self.variant_id {
+            Some(_) => AppError::VariantNotFound,
+            None => AppError::ProductNotFound,
+        })
+    }
+
+    // Holds `quantity` more, false when less than that is left unreserved;
+    pub async fn reserve(self, db: &impl ConnectionTrait, quantity: i32) -> APIResult<bool> {
+        match self.variant_id {
+            Some(v) => reserve::<ProductVariant>(db, v, quantity).await,
+            None => reserve::<Product>(db, self.product_id, quantity).await,
+        }
+    }
+
+    pub async fn release(self, db: &impl ConnectionTrait, quantity: i32) -> APIResult<()> {
+        match self.variant_id {
+            Some(v) => release::<ProductVariant>(db, v, quantity).await,
+            None => release::<Product>(db, self.product_id, quantity).await,
+        }
+    }
+
+    // Takes `quantity` out of the stock while releasing the `held` reservation,
+    // false when the stock left unreserved by others is not enough;
+    pub async fn consume(
+        self,
+        db: &impl ConnectionTrait,
+        quantity: i32,
+        held: i32,
+    ) -> APIResult<bool> {
+        match self.variant_id {
+            Some(v) => consume::<ProductVariant>(db, v, quantity, held).await,
+            None => consume::<Product>(db, self.product_id, quantity, held).await,
+        }
+    }
+
+    // Adds `quantity` (negative to remove) to the stock, false if it would drop below the reserved stock;
+    pub async fn adjust(self, db: &impl ConnectionTrait, quantity: i32) -> APIResult<bool> {
+        match self.variant_id {
+            Some(v) => adjust::<ProductVariant>(db, v, quantity).await,
+            None => adjust::<Product>(db, self.product_id, quantity).await,
+        }
+    }
+}
+
+async fn levels<E: Stocked>(db: &impl ConnectionTrait, id: i32) -> APIResult<Option<StockLevels>> {
+    Ok(E::find()
+        .select_only()
+        .column_as(E::stock_column(), "stock")
+        .column_as(E::reserved_stock_column(), "reserved_stock")
+        .filter(E::id_column().eq(id))
+        .into_model::<StockLevels>()
+        .one(db)
+        .await?)
+}
+
+// The stock nobody holds, `held` being added back for the holder's own reservation;
+fn unreserved<E: Stocked>(held: i32) -> SimpleExpr {
+    Expr::col(E::stock_column())
+        .into_simple_expr()
+        .sub(Expr::col(E::reserved_stock_column()))
+        .add(held)
+}
+
+async fn reserve<E: Stocked>(db: &impl ConnectionTrait, id: i32, quantity: i32) -> APIResult<bool> {
+    // Conditional increment, the row is only touched while enough unreserved stock is left;
+    let reserved = E::update_many()
+        .col_expr(
+            E::reserved_stock_column(),
+            Expr::col(E::reserved_stock_column()).add(quantity),
+        )
+        .filter(E::id_column().eq(id))
+        .filter(Expr::expr(unreserved::<E>(0)).gte(quantity))
+        .exec(db)
+        .await?;
+
+    Ok(reserved.rows_affected > 0)
+}
+
+async fn release<E: Stocked>(db: &impl ConnectionTrait, id: i32, quantity: i32) -> APIResult<()> {
+    E::update_many()
+        .col_expr(
+            E::reserved_stock_column(),
+            Expr::col(E::reserved_stock_column()).sub(quantity),
+        )
+        .filter(E::id_column().eq(id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+async fn consume<E: Stocked>(
+    db: &impl ConnectionTrait,
+    id: i32,
+    quantity: i32,
+    held: i32,
+) -> APIResult<bool> {
+    let consumed = E::update_many()
+        .col_expr(
+            E::stock_column(),
+            Expr::col(E::stock_column()).sub(quantity),
+        )
+        .col_expr(
+            E::reserved_stock_column(),
+            Expr::col(E::reserved_stock_column()).sub(held),
+        )
+        .filter(E::id_column().eq(id))
+        .filter(Expr::expr(unreserved::<E>(held)).gte(quantity))
+        .exec(db)
+        .await?;
+
+    Ok(consumed.rows_affected > 0)
+}
+
+async fn adjust<E: Stocked>(db: &impl ConnectionTrait, id: i32, quantity: i32) -> APIResult<bool> {
+    // Stock that is currently held by reservations cannot be taken away;
+    let adjusted = E::update_many()
+        .col_expr(
+            E::stock_column(),
+            Expr::col(E::stock_column()).add(quantity),
+        )
+        .col_expr(
+            E::updated_at_column(),
+            Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+        )
+        .filter(E::id_column().eq(id))
+        .filter(Expr::expr(unreserved::<E>(quantity)).gte(0))
+        .exec(db)
+        .await?;
+
+    Ok(adjusted.rows_affected > 0)
+}
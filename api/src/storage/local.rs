@@ -0,0 +1,61 @@
+use axum::async_trait;
+use std::{io::ErrorKind, path::PathBuf};
+use tokio::fs;
+
+use super::{check_key, join_url, Storage, StorageError};
+
+// Files under a directory of the server, served back by the /uploads route (see routes::upload_routes);
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, public_url: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            public_url: public_url.into(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        check_key(key)?;
+
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(
+        &self,
+        key: &str,
+        _content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Written next to the target then renamed, so a reader never sees a half written file;
+        let partial = path.with_extension("partial");
+        fs::write(&partial, bytes).await?;
+        fs::rename(&partial, &path).await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        join_url(&self.public_url, key)
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{async_trait, http::StatusCode};
+use std::{fmt, sync::Arc};
+use thiserror::Error;
+
+use crate::config::{StorageBackend, StorageConfig};
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+#[cfg(test)]
+pub(crate) use s3::Signer;
+
+// Where the local storage directory is served from when public_url is left empty;
+pub const LOCAL_STORAGE_ROUTE: &str = "/uploads";
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Invalid storage key {0:?}")]
+    InvalidKey(String),
+    #[error("Invalid storage endpoint {0:?}")]
+    InvalidEndpoint(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Request(#[from] hyper::http::Error),
+    #[error(transparent)]
+    Http(#[from] hyper::Error),
+    #[error("Storage responded with {0}: {1}")]
+    Status(StatusCode, String),
+}
+
+// Backend keeping the uploaded files, keys are relative paths such as "products/1/abc.png";
+// 1. put overwrites whatever is stored under the key;
+// 2. deleting a key that does not exist is not an error;
+// 3. url is where clients download the object from, it never touches the backend;
+#[async_trait]
+pub trait Storage: fmt::Debug + Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, bytes: Vec<u8>) -> Result<(), StorageError>;
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    fn url(&self, key: &str) -> String;
+}
+
+// Builds the configured backend;
+pub fn from_config(config: &StorageConfig) -> Result<Arc<dyn Storage>, StorageError> {
+    Ok(match config.backend {
+        StorageBackend::Local => local(config),
+        StorageBackend::S3 => Arc::new(S3Storage::new(&config.s3, &config.public_url)?),
+    })
+}
+
+// The local backend of the config whatever its `backend` says, it cannot fail to build;
+pub fn local(config: &StorageConfig) -> Arc<dyn Storage> {
+    let public_url = match config.public_url.as_str() {
+        "" => LOCAL_STORAGE_ROUTE,
+        url => url,
+    };
+
+    Arc::new(LocalStorage::new(&config.local_dir, public_url))
+}
+
+// Keys are generated by the API, this keeps a key from escaping the storage root all the same;
+fn check_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+fn join_url(base: &str, path: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), path)
+}
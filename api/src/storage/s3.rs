@@ -0,0 +1,235 @@
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use hyper::{
+    body::to_bytes,
+    client::HttpConnector,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Body, Client, Method, Request, StatusCode, Uri,
+};
+use hyper_tls::HttpsConnector;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt};
+
+use super::{check_key, join_url, Storage, StorageError};
+use crate::config::S3Config;
+
+// Objects of a bucket on an S3-compatible service, addressed path-style as <endpoint>/<bucket>/<key>;
+// Requests are signed with AWS Signature Version 4, the only scheme every S3-compatible service accepts;
+pub struct S3Storage {
+    endpoint: Uri,
+    bucket: String,
+    public_url: String,
+    signer: Signer,
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+// Leaves the credentials out;
+impl fmt::Debug for S3Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Storage")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("public_url", &self.public_url)
+            .finish()
+    }
+}
+
+impl S3Storage {
+    // An empty public_url serves the objects straight from the bucket;
+    pub fn new(config: &S3Config, public_url: &str) -> Result<Self, StorageError> {
+        let endpoint: Uri = config
+            .endpoint
+            .parse()
+            .map_err(|_| StorageError::InvalidEndpoint(config.endpoint.clone()))?;
+
+        let public_url = match public_url {
+            "" => join_url(&config.endpoint, &config.bucket),
+            url => url.to_string(),
+        };
+
+        Ok(Self {
+            endpoint,
+            bucket: config.bucket.clone(),
+            public_url,
+            signer: Signer {
+                access_key_id: config.access_key_id.clone(),
+                secret_access_key: config.secret_access_key.clone(),
+                region: config.region.clone(),
+            },
+            client: Client::builder().build(HttpsConnector::new()),
+        })
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        content_type: Option<&str>,
+        body: Vec<u8>,
+    ) -> Result<StatusCode, StorageError> {
+        check_key(key)?;
+
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket),
+            uri_encode(key)
+        );
+        let host = self
+            .endpoint
+            .authority()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = Utc::now();
+
+        let mut headers = BTreeMap::from([
+            ("host".to_string(), host.clone()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date(now)),
+        ]);
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE.to_string(), content_type.to_string());
+        }
+
+        let authorization =
+            self.signer
+                .authorization(&method, &path, "", &headers, &payload_hash, now);
+
+        let mut request = Request::builder().method(method).uri(format!(
+            "{}://{}{}",
+            self.endpoint.scheme_str().unwrap_or("https"),
+            host,
+            path
+        ));
+        for (name, value) in &headers {
+            request = request.header(name, value);
+        }
+        let request = request
+            .header(AUTHORIZATION, authorization)
+            .body(Body::from(body))?;
+
+        let response = self.client.request(request).await?;
+        let status = response.status();
+
+        if status.is_success() || status == StatusCode::NOT_FOUND {
+            return Ok(status);
+        }
+
+        let body = to_bytes(response.into_body()).await?;
+
+        Err(StorageError::Status(
+            status,
+            String::from_utf8_lossy(&body).into_owned(),
+        ))
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        match self
+            .send(Method::PUT, key, Some(content_type), bytes)
+            .await?
+        {
+            StatusCode::NOT_FOUND => Err(StorageError::Status(
+                StatusCode::NOT_FOUND,
+                format!("Bucket {} not found", self.bucket),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.send(Method::DELETE, key, None, Vec::new()).await?;
+
+        Ok(())
+    }
+
+    fn url(&self, key: &str) -> String {
+        join_url(&self.public_url, &uri_encode(key))
+    }
+}
+
+// https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+#[derive(Clone)]
+pub(crate) struct Signer {
+    pub(crate) access_key_id: String,
+    pub(crate) secret_access_key: String,
+    pub(crate) region: String,
+}
+
+impl Signer {
+    // `headers` maps the lowercase name to the value of every signed header, host included;
+    // `path` is already URI encoded and `query` already in canonical form;
+    pub(crate) fn authorization(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        headers: &BTreeMap<String, String>,
+        payload_hash: &str,
+        time: DateTime<Utc>,
+    ) -> String {
+        let date = time.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date(time),
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [date.as_str(), &self.region, "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+fn amz_date(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Every byte but the unreserved characters and the path separators is percent encoded, as SigV4 expects;
+fn uri_encode(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,201 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Request, StatusCode},
+    middleware::{self, Next},
+    routing::get,
+    Extension, Json, Router,
+};
+use serde_json::{json, Value};
+
+use super::fixtures::UserFixture;
+use super::TestApp;
+use crate::{user_auth_required, AppConfig, AppState, ConfigError, CurrentUser};
+
+async fn whoami(
+    State(state): State<AppState>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Json<Value> {
+    Json(json!({
+        "user_id": current_user.id,
+        "bcrypt_cost": state.config().auth.bcrypt_cost,
+    }))
+}
+
+fn extra_routes() -> Router<AppState> {
+    Router::new()
+        .route("/whoami", get(whoami))
+        .route_layer(middleware::from_fn(user_auth_required))
+}
+
+#[tokio::test]
+async fn builder_mounts_extra_routes_with_app_state() {
+    let app = TestApp::with_builder(|builder| builder.nest("/extra", extra_routes())).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, body) = app.get("/extra/whoami", customer.token()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user_id"], customer.id());
+    assert_eq!(body["bcrypt_cost"], 4);
+
+    let (status, _) = app.get("/extra/whoami", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The built-in routes are still there;
+    let (status, _) = app.get("/health/live", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+async fn maintenance_mode<B>(_req: Request<B>, _next: Next<B>) -> StatusCode {
+    StatusCode::SERVICE_UNAVAILABLE
+}
+
+#[tokio::test]
+async fn builder_layers_wrap_previously_merged_routes() {
+    let app = TestApp::with_builder(|builder| {
+        builder
+            .layer(middleware::from_fn(maintenance_mode))
+            .merge(Router::new().route("/status", get(|| async { "up" })))
+    })
+    .await;
+
+    let (status, _) = app.get("/health/live", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    let (status, body) = app.get("/status", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "up");
+}
+
+#[tokio::test]
+async fn state_refuses_an_invalid_config() {
+    let app = TestApp::new().await;
+    let mut config = (*app.config).clone();
+    config.cors.allowed_origins = vec!["not an origin\n".to_string()];
+
+    let result = AppState::new(app.db.clone()).with_config(config);
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue("cors.allowed_origins", _))
+    ));
+}
+
+#[tokio::test]
+async fn purge_retention_is_bounded() {
+    let app = TestApp::new().await;
+    let mut config = (*app.config).clone();
+    config.catalog.purge_deleted_after_days = i64::MAX / 86_400;
+
+    let result = AppState::new(app.db.clone()).with_config(config.clone());
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue(
+            "catalog.purge_deleted_after_days",
+            _
+        ))
+    ));
+
+    config.catalog.purge_deleted_after_days = 36500;
+    let state = AppState::new(app.db.clone()).with_config(config).unwrap();
+    assert!(state.config.purge_retention().is_some());
+}
+
+#[tokio::test]
+async fn access_token_ttl_is_bounded() {
+    let app = TestApp::new().await;
+    let mut config = (*app.config).clone();
+    config.auth.access_token_ttl_minutes = i64::MAX;
+
+    let result = AppState::new(app.db.clone()).with_config(config.clone());
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue(
+            "auth.access_token_ttl_minutes",
+            _
+        ))
+    ));
+
+    config.auth.access_token_ttl_minutes = 525_600;
+    let state = AppState::new(app.db.clone()).with_config(config).unwrap();
+    assert_eq!(state.config.access_token_ttl().num_days(), 365);
+}
+
+#[tokio::test]
+async fn refresh_token_ttl_is_bounded() {
+    let app = TestApp::new().await;
+    let mut config = (*app.config).clone();
+    config.auth.refresh_token_ttl_days = i64::MAX;
+
+    let result = AppState::new(app.db.clone()).with_config(config.clone());
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue("auth.refresh_token_ttl_days", _))
+    ));
+
+    config.auth.refresh_token_ttl_days = 36500;
+    let state = AppState::new(app.db.clone()).with_config(config).unwrap();
+    assert_eq!(state.config.refresh_token_ttl().num_days(), 36500);
+}
+
+#[tokio::test]
+async fn reservation_ttl_is_bounded() {
+    let app = TestApp::new().await;
+    let mut config = (*app.config).clone();
+    config.inventory.reservation_ttl_minutes = i64::MAX;
+
+    let result = AppState::new(app.db.clone()).with_config(config.clone());
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue(
+            "inventory.reservation_ttl_minutes",
+            _
+        ))
+    ));
+
+    config.inventory.reservation_ttl_minutes = 525_600;
+    let state = AppState::new(app.db.clone()).with_config(config).unwrap();
+    assert_eq!(state.config.reservation_ttl().num_days(), 365);
+}
+
+#[test]
+fn in_memory_sqlite_keeps_a_single_connection() {
+    let mut config = AppConfig::default();
+    config.database.url = "sqlite::memory:".to_string();
+    config.database.max_connections = 10;
+    assert_eq!(config.connect_options().get_max_connections(), Some(1));
+
+    config.database.url = "sqlite://shop.db?mode=memory&cache=shared".to_string();
+    assert_eq!(config.connect_options().get_max_connections(), Some(1));
+
+    config.database.url = "sqlite://data/memory.db?mode=rwc".to_string();
+    assert_eq!(config.connect_options().get_max_connections(), Some(10));
+
+    config.database.url = "postgres://localhost/shop".to_string();
+    assert_eq!(config.connect_options().get_max_connections(), Some(10));
+}
+
+#[tokio::test]
+async fn cross_origin_requests_need_a_configured_origin() {
+    let from_shop = || {
+        Request::builder()
+            .uri("/health/live")
+            .header(header::ORIGIN, "https://shop.test")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let app = TestApp::new().await;
+    let (status, headers, _) = app.send(from_shop()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+    let app = TestApp::with_config(|config| {
+        config.cors.allowed_origins = vec!["https://shop.test".to_string()];
+    })
+    .await;
+    let (_, headers, _) = app.send(from_shop()).await;
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://shop.test"
+    );
+}
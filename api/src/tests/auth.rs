@@ -0,0 +1,168 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{UserFixture, PASSWORD};
+use super::TestApp;
+
+async fn login(app: &TestApp, email: &str) -> (StatusCode, serde_json::Value) {
+    app.post(
+        "/auth/login",
+        None,
+        json!({ "email": email, "password": PASSWORD }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn register_then_login() {
+    let app = TestApp::new().await;
+
+    let (status, _) = app
+        .post(
+            "/auth/register",
+            None,
+            json!({ "username": "bob", "email": "bob@test.com", "password": PASSWORD }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = login(&app, "bob@test.com").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["expires_in"],
+        app.config.access_token_ttl().num_seconds()
+    );
+
+    let token = body["token"].as_str().unwrap();
+    let (status, body) = app.get("/auth/persistent", Some(token)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["username"], "bob");
+    assert_eq!(body["data"]["role"], "customer");
+
+    let (status, body) = app.get("/auth/persistent", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "MISSING_TOKEN");
+}
+
+#[tokio::test]
+async fn register_rejects_duplicate_email() {
+    let app = TestApp::new().await;
+    let bob = UserFixture::customer("bob");
+    let email = bob.email();
+    bob.create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/auth/register",
+            None,
+            json!({ "username": "bobby", "email": email, "password": PASSWORD }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn register_rejects_duplicates_ignoring_case() {
+    let app = TestApp::new().await;
+    let bob = UserFixture::customer("bob");
+    let email = bob.email();
+    bob.create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/auth/register",
+            None,
+            json!({ "username": "BOB", "email": "other@test.com", "password": PASSWORD }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "DUPLICATE_USERNAME");
+
+    let (status, body) = app
+        .post(
+            "/auth/register",
+            None,
+            json!({ "username": "bobby", "email": email.to_uppercase(), "password": PASSWORD }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "DUPLICATE_EMAIL");
+}
+
+#[tokio::test]
+async fn login_rejects_wrong_password() {
+    let app = TestApp::new().await;
+    let bob = UserFixture::customer("bob");
+    let email = bob.email();
+    bob.create(&app).await;
+
+    let (status, _) = app
+        .post(
+            "/auth/login",
+            None,
+            json!({ "email": email, "password": "Wr0ngpassword" }),
+        )
+        .await;
+
+    assert_ne!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn refresh_token_rotates_and_detects_reuse() {
+    let app = TestApp::new().await;
+    let bob = UserFixture::customer("bob");
+    let email = bob.email();
+    bob.create(&app).await;
+
+    let (_, body) = login(&app, &email).await;
+    let first = body["refresh_token"].as_str().unwrap().to_string();
+
+    let (status, body) = app
+        .post("/auth/refresh", None, json!({ "refresh_token": first }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let second = body["refresh_token"].as_str().unwrap().to_string();
+    assert_ne!(first, second);
+
+    // Replaying the rotated token revokes the whole family, including the newest token;
+    let (status, _) = app
+        .post("/auth/refresh", None, json!({ "refresh_token": first }))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app
+        .post("/auth/refresh", None, json!({ "refresh_token": second }))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn logout_revokes_refresh_token() {
+    let app = TestApp::new().await;
+    let bob = UserFixture::customer("bob");
+    let email = bob.email();
+    bob.create(&app).await;
+
+    let (_, body) = login(&app, &email).await;
+    let refresh_token = body["refresh_token"].as_str().unwrap();
+
+    let (status, _) = app
+        .post(
+            "/auth/logout",
+            None,
+            json!({ "refresh_token": refresh_token }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .post(
+            "/auth/refresh",
+            None,
+            json!({ "refresh_token": refresh_token }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
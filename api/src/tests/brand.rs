@@ -0,0 +1,82 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{self, UserFixture};
+use super::TestApp;
+
+#[tokio::test]
+async fn admin_creates_and_finds_brands() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, _) = app
+        .post("/brands/create", admin.token(), json!({ "name": "Acme" }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    fixtures::brand(&app, "Globex").await;
+
+    let (status, body) = app.get("/brands/find?keyword=acm", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "Acme");
+}
+
+#[tokio::test]
+async fn brand_names_are_unique_ignoring_case() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    fixtures::brand(&app, "Acme").await;
+
+    let (status, body) = app
+        .post("/brands/create", admin.token(), json!({ "name": "ACME" }))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "DUPLICATE_BRAND");
+}
+
+#[tokio::test]
+async fn brand_management_requires_admin() {
+    let app = TestApp::new().await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, _) = app
+        .post("/brands/create", None, json!({ "name": "Acme" }))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app
+        .post(
+            "/brands/create",
+            customer.token(),
+            json!({ "name": "Acme" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn deleted_brand_is_hidden_until_restored() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+
+    let (status, _) = app
+        .delete(&format!("/brands/delete/{}", brand.id), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/brands/find", None).await;
+    assert_eq!(body["total_items"], 0);
+
+    let (status, _) = app
+        .patch(
+            &format!("/brands/restore/{}", brand.id),
+            admin.token(),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/brands/find", None).await;
+    assert_eq!(body["total_items"], 1);
+}
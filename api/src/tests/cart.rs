@@ -0,0 +1,117 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use migration::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Value};
+use serde_json::json;
+
+use ::entity::prelude::StockReservation;
+use ::entity::stock_reservation;
+
+use super::fixtures::{self, UserFixture};
+use super::TestApp;
+use crate::services::ReservationService;
+
+#[tokio::test]
+async fn cart_requires_authentication() {
+    let app = TestApp::new().await;
+
+    let (status, _) = app.get("/carts/find", None).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn adding_to_cart_reserves_stock() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, _) = app
+        .post(
+            "/carts/create-or-update",
+            customer.token(),
+            json!({ "product_id": product.id, "quantity": 3 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["reserved_stock"], 3);
+    assert_eq!(body["data"][0]["available_stock"], 7);
+
+    // Updating the quantity moves the reservation instead of stacking a new one;
+    app.post(
+        "/carts/create-or-update",
+        customer.token(),
+        json!({ "product_id": product.id, "quantity": 1 }),
+    )
+    .await;
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["reserved_stock"], 1);
+}
+
+#[tokio::test]
+async fn cart_rejects_quantity_above_available_stock() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 5).await;
+    let alice = UserFixture::customer("alice").create(&app).await;
+    let bob = UserFixture::customer("bob").create(&app).await;
+    fixtures::cart(&app, alice.id(), product.id, 4).await;
+
+    let (status, body) = app
+        .post(
+            "/carts/create-or-update",
+            bob.token(),
+            json!({ "product_id": product.id, "quantity": 2 }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn find_cart_returns_only_own_items_with_subtotal() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let alice = UserFixture::customer("alice").create(&app).await;
+    let bob = UserFixture::customer("bob").create(&app).await;
+    fixtures::cart(&app, alice.id(), product.id, 2).await;
+
+    let (status, body) = app.get("/carts/find", alice.token()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["subtotal"]["amount"], 3000);
+
+    let (_, body) = app.get("/carts/find", bob.token()).await;
+    assert_eq!(body["total_items"], 0);
+}
+
+#[tokio::test]
+async fn expired_reservations_are_released() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let alice = UserFixture::customer("alice").create(&app).await;
+    let bob = UserFixture::customer("bob").create(&app).await;
+    fixtures::cart(&app, alice.id(), product.id, 3).await;
+    fixtures::cart(&app, bob.id(), product.id, 2).await;
+
+    // Only alice's reservation is past its expiry;
+    StockReservation::update_many()
+        .col_expr(
+            stock_reservation::Column::ExpiresAt,
+            Expr::value(Value::from(Utc::now() - Duration::minutes(1))),
+        )
+        .filter(stock_reservation::Column::UserId.eq(alice.id()))
+        .exec(&app.db)
+        .await
+        .unwrap();
+
+    let released = ReservationService::release_expired(&app.db).await.unwrap();
+    assert_eq!(released, 1);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["reserved_stock"], 2);
+    assert_eq!(body["data"][0]["available_stock"], 8);
+}
@@ -0,0 +1,95 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{self, ProductFixture};
+use super::TestApp;
+use crate::CascadePolicy;
+
+#[tokio::test]
+async fn restrict_refuses_deleting_a_brand_with_active_products() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let uri = format!("/brands/delete/{}", product.brand_id);
+    let (status, body) = app.delete(&uri, admin.token()).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "BRAND_IN_USE");
+
+    // The brand's soft delete is rolled back with the refusal;
+    let (_, body) = app.get("/brands/find", None).await;
+    assert_eq!(body["total_items"], 1);
+
+    let (status, _) = app
+        .delete(&format!("/products/delete/{}", product.id), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app.delete(&uri, admin.token()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn cascade_deletes_and_restores_a_category_with_its_products() {
+    let app = TestApp::with_config(|config| {
+        config.catalog.category_delete_policy = CascadePolicy::Cascade;
+    })
+    .await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let retired = ProductFixture::new(product.category_id, product.brand_id)
+        .name("Saw")
+        .create(&app, admin.id())
+        .await;
+    let (status, _) = app
+        .delete(&format!("/products/delete/{}", retired.id), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .delete(
+            &format!("/categories/delete/{}", product.category_id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["total_items"], 0);
+
+    let (status, _) = app
+        .patch(
+            &format!("/categories/restore/{}", product.category_id),
+            admin.token(),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only the product deleted along with the category comes back;
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "Hammer");
+    assert_eq!(body["data"][0]["category_name"], "Tools");
+}
+
+#[tokio::test]
+async fn detach_keeps_products_listed_without_the_brand() {
+    let app = TestApp::with_config(|config| {
+        config.catalog.brand_delete_policy = CascadePolicy::Detach;
+    })
+    .await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let (status, _) = app
+        .delete(
+            &format!("/brands/delete/{}", product.brand_id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.get("/products/find", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["brand_name"], json!(null));
+    assert_eq!(body["data"][0]["category_name"], "Tools");
+}
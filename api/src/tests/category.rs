@@ -0,0 +1,270 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use ::entity::prelude::Category;
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+use crate::services::SoftDeleteService;
+
+#[tokio::test]
+async fn admin_creates_and_finds_categories() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, _) = app
+        .post(
+            "/categories/create",
+            admin.token(),
+            json!({ "name": "Tools" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    fixtures::category(&app, "Garden").await;
+
+    let (status, body) = app.get("/categories/find?keyword=too", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "Tools");
+}
+
+#[tokio::test]
+async fn category_management_requires_admin() {
+    let app = TestApp::new().await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, _) = app
+        .post("/categories/create", None, json!({ "name": "Tools" }))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = app
+        .post(
+            "/categories/create",
+            customer.token(),
+            json!({ "name": "Tools" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn deleted_category_is_hidden_until_restored() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    let (status, _) = app
+        .delete(
+            &format!("/categories/delete/{}", category.id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/categories/find", None).await;
+    assert_eq!(body["total_items"], 0);
+
+    let (status, _) = app
+        .patch(
+            &format!("/categories/restore/{}", category.id),
+            admin.token(),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/categories/find", None).await;
+    assert_eq!(body["total_items"], 1);
+}
+
+#[tokio::test]
+async fn categories_are_returned_as_a_tree_with_breadcrumbs() {
+    let app = TestApp::new().await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    let phones = fixtures::subcategory(&app, "Phones", electronics.id).await;
+    let accessories = fixtures::subcategory(&app, "Accessories", phones.id).await;
+    fixtures::subcategory(&app, "Laptops", electronics.id).await;
+    fixtures::category(&app, "Tools").await;
+
+    let (status, body) = app.get("/categories/tree", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"][0]["name"], "Electronics");
+    assert_eq!(body["data"][0]["children"][0]["name"], "Laptops");
+    assert_eq!(body["data"][0]["children"][1]["name"], "Phones");
+    assert_eq!(
+        body["data"][0]["children"][1]["children"][0]["name"],
+        "Accessories"
+    );
+    assert_eq!(body["data"][1]["name"], "Tools");
+
+    let (status, body) = app
+        .get(&format!("/categories/breadcrumbs/{}", accessories.id), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Electronics", "Phones", "Accessories"]);
+}
+
+#[tokio::test]
+async fn category_cannot_be_moved_under_its_own_subtree() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    let phones = fixtures::subcategory(&app, "Phones", electronics.id).await;
+    let accessories = fixtures::subcategory(&app, "Accessories", phones.id).await;
+
+    for parent in [electronics.id, accessories.id] {
+        let (status, body) = app
+            .patch(
+                &format!("/categories/move/{}", electronics.id),
+                admin.token(),
+                json!({ "parent_id": parent }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_CATEGORY_PARENT");
+    }
+
+    // Accessories comes along with Phones;
+    let (status, _) = app
+        .patch(
+            &format!("/categories/move/{}", phones.id),
+            admin.token(),
+            json!({ "parent_id": null }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app
+        .get(&format!("/categories/breadcrumbs/{}", accessories.id), None)
+        .await;
+    assert_eq!(body["data"][0]["name"], "Phones");
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn category_with_subcategories_cannot_be_deleted() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    fixtures::subcategory(&app, "Phones", electronics.id).await;
+
+    let (status, body) = app
+        .delete(
+            &format!("/categories/delete/{}", electronics.id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "CATEGORY_HAS_SUBCATEGORIES");
+}
+
+#[tokio::test]
+async fn subcategory_is_restored_only_after_its_parent() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    let phones = fixtures::subcategory(&app, "Phones", electronics.id).await;
+
+    for id in [phones.id, electronics.id] {
+        let (status, _) = app
+            .delete(&format!("/categories/delete/{}", id), admin.token())
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = app
+        .patch(
+            &format!("/categories/restore/{}", phones.id),
+            admin.token(),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "CATEGORY_PARENT_DELETED");
+
+    for id in [electronics.id, phones.id] {
+        let (status, _) = app
+            .patch(
+                &format!("/categories/restore/{}", id),
+                admin.token(),
+                json!({}),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = app
+        .get(&format!("/categories/breadcrumbs/{}", phones.id), None)
+        .await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn breadcrumbs_stop_at_a_deleted_ancestor() {
+    let app = TestApp::new().await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    let phones = fixtures::subcategory(&app, "Phones", electronics.id).await;
+    let accessories = fixtures::subcategory(&app, "Accessories", phones.id).await;
+    // Bypasses the subcategory check, like rows left over from before it existed;
+    SoftDeleteService::soft_delete::<Category>(&app.db, phones.id)
+        .await
+        .unwrap();
+
+    let (status, body) = app
+        .get(&format!("/categories/breadcrumbs/{}", accessories.id), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["name"], "Accessories");
+}
+
+#[tokio::test]
+async fn products_are_filtered_by_category_subtree() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let electronics = fixtures::category(&app, "Electronics").await;
+    let phones = fixtures::subcategory(&app, "Phones", electronics.id).await;
+    let accessories = fixtures::subcategory(&app, "Accessories", phones.id).await;
+    let tools = fixtures::category(&app, "Tools").await;
+
+    for (name, category) in [
+        ("Phone", &phones),
+        ("Case", &accessories),
+        ("Hammer", &tools),
+    ] {
+        ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .create(&app, admin.id())
+            .await;
+    }
+
+    let (status, body) = app
+        .get(
+            &format!("/products/find?category_id={}", electronics.id),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 2);
+
+    let (_, body) = app
+        .get(
+            &format!("/products/find?category_id={}", accessories.id),
+            None,
+        )
+        .await;
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "Case");
+
+    let (status, body) = app.get("/products/find?category_id=999", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "CATEGORY_NOT_FOUND");
+}
@@ -0,0 +1,128 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use sea_orm::{ConnectionTrait, Statement};
+use serde_json::json;
+
+use super::fixtures::{UserFixture, PASSWORD};
+use super::TestApp;
+use crate::middlewares::REQUEST_ID_HEADER;
+
+#[tokio::test]
+async fn validation_errors_are_reported_per_field() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app
+        .post(
+            "/auth/register",
+            None,
+            json!({ "username": "bob", "email": "not-an-email", "password": "short" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+    assert_eq!(body["status"], 400);
+    assert_eq!(body["errors"]["email"], json!(["email"]));
+    assert!(body["errors"]["password"][0]
+        .as_str()
+        .unwrap()
+        .starts_with("Password must consist"));
+    assert!(body["errors"].get("username").is_none());
+}
+
+#[tokio::test]
+async fn domain_errors_carry_a_stable_code() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app
+        .post(
+            "/auth/login",
+            None,
+            json!({ "email": "nobody@test.com", "password": PASSWORD }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "WRONG_CREDENTIALS");
+    assert_eq!(body["status"], 401);
+    // Only validation failures list field errors;
+    assert!(body.get("errors").is_none());
+}
+
+#[tokio::test]
+async fn missing_resources_are_not_found() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, body) = app.delete("/products/delete/999", admin.token()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "PRODUCT_NOT_FOUND");
+    assert_eq!(body["status"], 404);
+
+    let (status, body) = app.get("/orders/999", admin.token()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "ORDER_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn internal_errors_do_not_leak_details() {
+    let app = TestApp::new().await;
+
+    let backend = app.db.get_database_backend();
+    app.db
+        .execute(Statement::from_string(
+            backend,
+            "DROP TABLE product".to_owned(),
+        ))
+        .await
+        .unwrap();
+
+    let (status, body) = app.get("/products/find", None).await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], "DATABASE_ERROR");
+    assert_eq!(body["message"], "Server error. Sorry for the inconvenience");
+    assert!(body["request_id"].is_string());
+    assert!(!body.to_string().contains("product"));
+}
+
+#[tokio::test]
+async fn auth_middleware_answers_with_the_error_envelope() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app.get("/carts/find", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "MISSING_TOKEN");
+
+    let (status, body) = app.get("/carts/find", Some("not-a-jwt")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "INVALID_TOKEN");
+}
+
+#[tokio::test]
+async fn request_id_is_echoed_in_header_and_error_body() {
+    let app = TestApp::new().await;
+
+    let request = Request::get("/carts/find")
+        .header(&REQUEST_ID_HEADER, "trace-123")
+        .body(Body::empty())
+        .unwrap();
+    let (_, headers, body) = app.send(request).await;
+
+    assert_eq!(headers[&REQUEST_ID_HEADER], "trace-123");
+    assert_eq!(body["request_id"], "trace-123");
+
+    // Without (or with an unusable) incoming id one is generated;
+    let request = Request::get("/carts/find")
+        .header(&REQUEST_ID_HEADER, "not valid!")
+        .body(Body::empty())
+        .unwrap();
+    let (_, headers, body) = app.send(request).await;
+
+    let generated = headers[&REQUEST_ID_HEADER].to_str().unwrap();
+    assert_ne!(generated, "not valid!");
+    assert_eq!(body["request_id"], generated);
+}
@@ -0,0 +1,94 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use serde_json::json;
+
+use super::fixtures::UserFixture;
+use super::TestApp;
+
+fn raw_post(uri: &str, token: &str, content_type: Option<&str>, body: &str) -> Request<Body> {
+    let mut builder = Request::post(uri).header(header::AUTHORIZATION, format!("Bearer {}", token));
+
+    if let Some(content_type) = content_type {
+        builder = builder.header(header::CONTENT_TYPE, content_type);
+    }
+
+    builder.body(Body::from(body.to_string())).unwrap()
+}
+
+#[tokio::test]
+async fn json_rejections_use_the_error_envelope() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let token = admin.token().unwrap();
+
+    let request = raw_post(
+        "/categories/create",
+        token,
+        Some("application/json"),
+        "{\"name\":",
+    );
+    let (status, _, body) = app.send(request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_BODY_SYNTAX");
+
+    let request = raw_post(
+        "/categories/create",
+        token,
+        Some("application/json"),
+        "{\"name\":1}",
+    );
+    let (status, _, body) = app.send(request).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "INVALID_BODY_TYPE");
+
+    let request = raw_post("/categories/create", token, None, "{\"name\":\"Tools\"}");
+    let (status, _, body) = app.send(request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "MISSING_CONTENT_TYPE");
+}
+
+#[tokio::test]
+async fn path_and_query_rejections_use_the_error_envelope() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, body) = app.delete("/products/delete/abc", admin.token()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "INVALID_PATH");
+
+    let (status, body) = app.get("/categories/find?page=first", None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "INVALID_QUERY");
+
+    // An empty value is not taken for a missing one, on the product listing either;
+    for uri in [
+        "/brands/find?page=",
+        "/products/find?page=",
+        "/products/find?brand_id[]=",
+    ] {
+        let (status, body) = app.get(uri, None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", uri);
+        assert_eq!(body["code"], "INVALID_QUERY");
+    }
+}
+
+#[tokio::test]
+async fn validated_json_runs_the_payload_rules() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/products/create",
+            admin.token(),
+            json!({ "name": "Hammer" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+    assert_eq!(body["errors"]["price"], json!(["Price is required"]));
+    assert_eq!(body["errors"]["brand_id"], json!(["Brand_id is required"]));
+}
@@ -0,0 +1,216 @@
+use chrono::Utc;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use sea_orm::{ActiveModelTrait, Set};
+use std::{collections::BTreeMap, io::Cursor};
+
+use ::entity::{brand, category, product, product_variant, sea_orm_active_enums::UserRole, user};
+
+use super::TestApp;
+use crate::handler::product::{CreateProductData, CreateVariantData};
+use crate::services::{BrandService, CartService, CategoryService, ProductService};
+use crate::utils::{encryption::hash_password, jwt::generate_token};
+
+pub const PASSWORD: &str = "Ab1cdefghijk";
+
+pub struct TestUser {
+    pub model: user::Model,
+    pub token: String,
+}
+
+impl TestUser {
+    pub fn id(&self) -> i32 {
+        self.model.id
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        Some(&self.token)
+    }
+}
+
+pub struct UserFixture {
+    username: String,
+    role: UserRole,
+}
+
+impl UserFixture {
+    pub fn customer(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            role: UserRole::Customer,
+        }
+    }
+
+    pub fn admin(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            role: UserRole::Admin,
+        }
+    }
+
+    pub fn email(&self) -> String {
+        format!("{}@test.com", self.username)
+    }
+
+    pub async fn create(self, app: &TestApp) -> TestUser {
+        let password = hash_password(PASSWORD.to_string(), app.config.auth.bcrypt_cost)
+            .await
+            .unwrap();
+
+        let model = user::ActiveModel {
+            email: Set(self.email()),
+            username: Set(self.username),
+            password: Set(password),
+            role: Set(self.role),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+
+        let token = generate_token(model.id, model.role, app.config.access_token_ttl()).unwrap();
+
+        TestUser { model, token }
+    }
+}
+
+pub async fn brand(app: &TestApp, name: &str) -> brand::Model {
+    BrandService::create(&app.db, name.to_string())
+        .await
+        .unwrap()
+}
+
+pub async fn category(app: &TestApp, name: &str) -> category::Model {
+    CategoryService::create(&app.db, name.to_string(), None)
+        .await
+        .unwrap()
+}
+
+pub async fn subcategory(app: &TestApp, name: &str, parent_id: i32) -> category::Model {
+    CategoryService::create(&app.db, name.to_string(), Some(parent_id))
+        .await
+        .unwrap()
+}
+
+pub struct ProductFixture {
+    data: CreateProductData,
+}
+
+impl ProductFixture {
+    pub fn new(category_id: i32, brand_id: i32) -> Self {
+        Self {
+            data: CreateProductData {
+                name: "Hammer".to_string(),
+                price: 1500,
+                currency: None,
+                stock: 10,
+                category_id,
+                brand_id,
+                description: None,
+            },
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.data.name = name.to_string();
+        self
+    }
+
+    pub fn price(mut self, price: i64) -> Self {
+        self.data.price = price;
+        self
+    }
+
+    pub fn currency(mut self, currency: &str) -> Self {
+        self.data.currency = Some(currency.to_string());
+        self
+    }
+
+    pub fn stock(mut self, stock: i32) -> Self {
+        self.data.stock = stock;
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.data.description = Some(description.to_string());
+        self
+    }
+
+    // The initial restock is recorded in the inventory ledger under `created_by`;
+    pub async fn create(self, app: &TestApp, created_by: i32) -> product::Model {
+        ProductService::create(&app.db, self.data, created_by)
+            .await
+            .unwrap()
+    }
+}
+
+// A variant of `product_id` with a single "size" option;
+pub async fn variant(
+    app: &TestApp,
+    product_id: i32,
+    sku: &str,
+    price: Option<i64>,
+    stock: i32,
+    created_by: i32,
+) -> product_variant::Model {
+    let data = CreateVariantData {
+        sku: sku.to_string(),
+        options: BTreeMap::from([("size".to_string(), sku.to_string())]),
+        price,
+        stock,
+    };
+
+    ProductService::create_variant(&app.db, product_id, data, created_by)
+        .await
+        .unwrap()
+}
+
+// Goes through CartService so the quantity is reserved like it would be through the API;
+pub async fn cart(app: &TestApp, user_id: i32, product_id: i32, quantity: i32) {
+    CartService::create_or_update(
+        &app.db,
+        user_id,
+        product_id,
+        None,
+        quantity,
+        app.config.reservation_ttl(),
+    )
+    .await
+    .unwrap();
+}
+
+// Admin plus one product of the given stock, the usual starting point of cart/order tests;
+pub async fn catalog(app: &TestApp, stock: i32) -> (TestUser, product::Model) {
+    let admin = UserFixture::admin("admin").create(app).await;
+    let brand = brand(app, "Acme").await;
+    let category = category(app, "Tools").await;
+    let product = ProductFixture::new(category.id, brand.id)
+        .stock(stock)
+        .create(app, admin.id())
+        .await;
+
+    (admin, product)
+}
+
+// A width x height image encoded as `format`, `noise` fills it with random pixels so it does not compress;
+pub fn image(width: u32, height: u32, format: ImageOutputFormat, noise: bool) -> Vec<u8> {
+    let pixels = RgbImage::from_fn(width, height, |x, y| {
+        if noise {
+            image::Rgb(rand::random())
+        } else {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        }
+    });
+
+    let mut bytes = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(pixels)
+        .write_to(&mut bytes, format)
+        .unwrap();
+
+    bytes.into_inner()
+}
+
+pub fn png(width: u32, height: u32) -> Vec<u8> {
+    image(width, height, ImageOutputFormat::Png, false)
+}
@@ -0,0 +1,24 @@
+use axum::http::StatusCode;
+
+use super::TestApp;
+
+#[tokio::test]
+async fn liveness_is_always_ok() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app.get("/health/live", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "alive");
+}
+
+#[tokio::test]
+async fn readiness_reports_database_and_migrations() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app.get("/health/ready", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["database"], "up");
+    assert_eq!(body["migrations"]["pending"].as_array().unwrap().len(), 0);
+}
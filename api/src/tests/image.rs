@@ -0,0 +1,275 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use image::ImageOutputFormat;
+use serde_json::{json, Value};
+
+use super::fixtures::{self, UserFixture};
+use super::TestApp;
+
+async fn fetch(app: &TestApp, url: &str) -> (StatusCode, Option<String>, Vec<u8>) {
+    let request = Request::builder().uri(url).body(Body::empty()).unwrap();
+    let (status, headers, bytes) = app.send_raw(request).await;
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string());
+
+    (status, content_type, bytes.to_vec())
+}
+
+async fn images(app: &TestApp, product_id: i32) -> Vec<Value> {
+    let (status, body) = app
+        .get(&format!("/products/{}/images", product_id), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    body["data"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn admin_uploads_image_with_thumbnail() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let uri = format!("/products/{}/images", product.id);
+    let (status, body) = app
+        .upload(&uri, admin.token(), "image/png", &fixtures::png(600, 400))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let image = &body["data"];
+    assert_eq!(image["width"], 600);
+    assert_eq!(image["height"], 400);
+    assert_eq!(image["position"], 0);
+    assert_eq!(image["is_primary"], true);
+    assert_eq!(image["content_type"], "image/png");
+
+    let url = image["url"].as_str().unwrap();
+    assert!(url.starts_with(&format!("/uploads/products/{}/", product.id)));
+    let (status, content_type, _) = fetch(&app, url).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("image/png"));
+
+    // The thumbnail fits a 256px square and keeps the aspect ratio;
+    let (status, _, bytes) = fetch(&app, image["thumbnail_url"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let thumbnail = image::load_from_memory(&bytes).unwrap();
+    assert_eq!(thumbnail.width(), 256);
+    assert!((170..=171).contains(&thumbnail.height()));
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["images"][0]["url"], url);
+}
+
+#[tokio::test]
+async fn jpeg_upload_gets_a_jpeg_thumbnail() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let jpeg = fixtures::image(300, 300, ImageOutputFormat::Jpeg(90), false);
+
+    let (status, body) = app
+        .upload(
+            &format!("/products/{}/images", product.id),
+            admin.token(),
+            "image/jpeg",
+            &jpeg,
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["data"]["url"].as_str().unwrap().ends_with(".jpg"));
+
+    let (_, content_type, _) = fetch(&app, body["data"]["thumbnail_url"].as_str().unwrap()).await;
+    assert_eq!(content_type.as_deref(), Some("image/jpeg"));
+}
+
+#[tokio::test]
+async fn upload_validates_content_type_and_content() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/{}/images", product.id);
+
+    let (status, body) = app
+        .upload(&uri, admin.token(), "image/gif", b"GIF89a")
+        .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body["code"], "UNSUPPORTED_IMAGE_TYPE");
+
+    // The bytes must be what the part claims to be;
+    let jpeg = fixtures::image(32, 32, ImageOutputFormat::Jpeg(90), false);
+    let (status, body) = app.upload(&uri, admin.token(), "image/png", &jpeg).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_IMAGE");
+
+    let mut truncated = fixtures::png(64, 64);
+    truncated.truncate(60);
+    let (status, body) = app
+        .upload(&uri, admin.token(), "image/png", &truncated)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_IMAGE");
+
+    assert!(images(&app, product.id).await.is_empty());
+}
+
+#[tokio::test]
+async fn upload_enforces_the_configured_size_limit() {
+    let app = TestApp::with_config(|config| config.storage.max_image_bytes = 1024).await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let (status, body) = app
+        .upload(
+            &format!("/products/{}/images", product.id),
+            admin.token(),
+            "image/png",
+            &fixtures::image(64, 64, ImageOutputFormat::Png, true),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"], "IMAGE_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn uploads_may_exceed_the_default_body_limit() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    // About 3MB, over axum's 2MB default but under the 5MB image limit;
+    let png = fixtures::image(1000, 1000, ImageOutputFormat::Png, true);
+    assert!(png.len() > 2 * 1024 * 1024);
+
+    let (status, body) = app
+        .upload(
+            &format!("/products/{}/images", product.id),
+            admin.token(),
+            "image/png",
+            &png,
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["data"]["size"], png.len());
+}
+
+#[tokio::test]
+async fn upload_requires_admin_and_a_file_part() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+    let uri = format!("/products/{}/images", product.id);
+
+    let (status, _) = app
+        .upload(&uri, customer.token(), "image/png", &fixtures::png(8, 8))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = app.post(&uri, admin.token(), json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_MULTIPART");
+
+    let (status, body) = app
+        .upload(
+            "/products/999/images",
+            admin.token(),
+            "image/png",
+            &fixtures::png(8, 8),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "PRODUCT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn images_are_reordered_and_primary_moves() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/{}/images", product.id);
+
+    let mut ids = Vec::new();
+    for size in [10, 20, 30] {
+        let (status, body) = app
+            .upload(&uri, admin.token(), "image/png", &fixtures::png(size, size))
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(body["data"]["id"].as_i64().unwrap());
+    }
+    let (first, second, third) = (ids[0], ids[1], ids[2]);
+
+    let (status, body) = app
+        .patch(
+            &format!("{}/order", uri),
+            admin.token(),
+            json!({ "image_ids": [first, second] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_IMAGE_ORDER");
+
+    let (status, _) = app
+        .patch(
+            &format!("{}/order", uri),
+            admin.token(),
+            json!({ "image_ids": [third, first, second] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .patch(
+            &format!("{}/{}/primary", uri, second),
+            admin.token(),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let listed = images(&app, product.id).await;
+    let order: Vec<i64> = listed.iter().map(|i| i["id"].as_i64().unwrap()).collect();
+    assert_eq!(order, vec![third, first, second]);
+    let primary: Vec<i64> = listed
+        .iter()
+        .filter(|i| i["is_primary"] == true)
+        .map(|i| i["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(primary, vec![second]);
+
+    // Deleting the primary image hands the flag to the first one left, its files are gone;
+    let deleted_url = listed[2]["url"].as_str().unwrap().to_string();
+    let (status, _) = app
+        .delete(&format!("{}/{}", uri, second), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let listed = images(&app, product.id).await;
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0]["id"], third);
+    assert_eq!(listed[0]["is_primary"], true);
+
+    let (status, _, _) = fetch(&app, &deleted_url).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = app
+        .delete(&format!("{}/{}", uri, second), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "IMAGE_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn concurrent_first_uploads_keep_a_single_primary() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/{}/images", product.id);
+    let (png, other) = (fixtures::png(16, 16), fixtures::png(24, 24));
+
+    let (first, second) = tokio::join!(
+        app.upload(&uri, admin.token(), "image/png", &png),
+        app.upload(&uri, admin.token(), "image/png", &other),
+    );
+    assert_eq!(first.0, StatusCode::CREATED);
+    assert_eq!(second.0, StatusCode::CREATED);
+
+    let listed = images(&app, product.id).await;
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed.iter().filter(|i| i["is_primary"] == true).count(), 1);
+}
@@ -0,0 +1,308 @@
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+
+struct Catalog {
+    acme: i32,
+    bolt: i32,
+    tools: i32,
+    power_tools: i32,
+    garden: i32,
+}
+
+// Created in this order:
+// Hammer  Tools        Acme    800
+// Drill   Power Tools  Bolt  12000  sold out, its whole stock sits in a cart
+// Saw     Tools        Bolt   3000
+// Rake    Garden       Acme   1500
+// Gloves  Garden       Bolt   2000  sold out, its only variant has no stock
+async fn catalog(app: &TestApp) -> Catalog {
+    let admin = UserFixture::admin("admin").create(app).await;
+    let customer = UserFixture::customer("bob").create(app).await;
+    let acme = fixtures::brand(app, "Acme").await;
+    let bolt = fixtures::brand(app, "Bolt").await;
+    let tools = fixtures::category(app, "Tools").await;
+    let power_tools = fixtures::subcategory(app, "Power Tools", tools.id).await;
+    let garden = fixtures::category(app, "Garden").await;
+
+    let product = |name: &str, category: i32, brand: i32, price: i64| {
+        ProductFixture::new(category, brand)
+            .name(name)
+            .price(price)
+            .stock(2)
+    };
+
+    product("Hammer", tools.id, acme.id, 800)
+        .create(app, admin.id())
+        .await;
+    let drill = product("Drill", power_tools.id, bolt.id, 12000)
+        .create(app, admin.id())
+        .await;
+    product("Saw", tools.id, bolt.id, 3000)
+        .create(app, admin.id())
+        .await;
+    product("Rake", garden.id, acme.id, 1500)
+        .create(app, admin.id())
+        .await;
+    let gloves = product("Gloves", garden.id, bolt.id, 2000)
+        .create(app, admin.id())
+        .await;
+
+    fixtures::cart(app, customer.id(), drill.id, 2).await;
+    fixtures::variant(app, gloves.id, "M", None, 0, admin.id()).await;
+
+    Catalog {
+        acme: acme.id,
+        bolt: bolt.id,
+        tools: tools.id,
+        power_tools: power_tools.id,
+        garden: garden.id,
+    }
+}
+
+async fn find(app: &TestApp, query: &str) -> Value {
+    let (status, body) = app.get(&format!("/products/find?{}", query), None).await;
+    assert_eq!(status, StatusCode::OK, "{}: {}", query, body);
+
+    body
+}
+
+async fn names(app: &TestApp, query: &str) -> Vec<String> {
+    let body = find(app, &format!("size=50&{}", query)).await;
+
+    body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn listing_filters_by_brand_category_price_and_stock() {
+    let app = TestApp::new().await;
+    let c = catalog(&app).await;
+
+    assert_eq!(
+        names(&app, &format!("brand_id[]={}", c.acme)).await,
+        ["Hammer", "Rake"]
+    );
+    assert_eq!(
+        names(
+            &app,
+            &format!("brand_id[]={}&brand_id[]={}", c.acme, c.bolt)
+        )
+        .await,
+        ["Hammer", "Drill", "Saw", "Rake", "Gloves"]
+    );
+
+    // A category brings its subcategories along;
+    assert_eq!(
+        names(&app, &format!("category_id[]={}", c.tools)).await,
+        ["Hammer", "Drill", "Saw"]
+    );
+    assert_eq!(
+        names(
+            &app,
+            &format!("category_id[]={}&category_id[]={}", c.power_tools, c.garden)
+        )
+        .await,
+        ["Drill", "Rake", "Gloves"]
+    );
+
+    // Both bounds are included;
+    assert_eq!(
+        names(&app, "min_price=1500&max_price=3000").await,
+        ["Saw", "Rake", "Gloves"]
+    );
+
+    // A product with variants is only in stock through them;
+    assert_eq!(
+        names(&app, "in_stock=true").await,
+        ["Hammer", "Saw", "Rake"]
+    );
+    assert_eq!(names(&app, "in_stock=false").await, ["Drill", "Gloves"]);
+
+    assert_eq!(
+        names(
+            &app,
+            &format!("brand_id[]={}&in_stock=true&max_price=2000", c.bolt)
+        )
+        .await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn listing_rejects_an_invalid_price_range() {
+    let app = TestApp::new().await;
+
+    for query in ["min_price=-1", "min_price=500&max_price=100"] {
+        let (status, body) = app.get(&format!("/products/find?{}", query), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_PRICE_RANGE");
+    }
+
+    let (status, body) = app.get("/products/find?sort=cheapest", None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "INVALID_QUERY");
+}
+
+#[tokio::test]
+async fn listing_sorts_by_price_date_and_name() {
+    let app = TestApp::new().await;
+    catalog(&app).await;
+
+    assert_eq!(
+        names(&app, "sort=price_asc").await,
+        ["Hammer", "Rake", "Gloves", "Saw", "Drill"]
+    );
+    assert_eq!(
+        names(&app, "sort=price_desc").await,
+        ["Drill", "Saw", "Gloves", "Rake", "Hammer"]
+    );
+    assert_eq!(
+        names(&app, "sort=newest").await,
+        ["Gloves", "Rake", "Saw", "Drill", "Hammer"]
+    );
+    assert_eq!(
+        names(&app, "sort=name").await,
+        ["Drill", "Gloves", "Hammer", "Rake", "Saw"]
+    );
+
+    // The sort applies before paging;
+    let body = find(&app, "sort=price_desc&size=2&page=2").await;
+    assert_eq!(body["data"][0]["name"], "Gloves");
+}
+
+#[tokio::test]
+async fn facets_count_the_results_without_their_own_filter() {
+    let app = TestApp::new().await;
+    let c = catalog(&app).await;
+
+    let body = find(&app, &format!("brand_id[]={}", c.acme)).await;
+    assert_eq!(body["total_items"], 2);
+
+    // Every brand is listed even though only Acme is picked;
+    assert_eq!(
+        body["facets"]["brands"],
+        json!([
+            { "id": c.bolt, "name": "Bolt", "count": 3 },
+            { "id": c.acme, "name": "Acme", "count": 2 },
+        ])
+    );
+    // The other facets only count Acme's products;
+    assert_eq!(
+        body["facets"]["categories"],
+        json!([
+            { "id": c.tools, "name": "Tools", "count": 1 },
+            { "id": c.garden, "name": "Garden", "count": 1 },
+        ])
+    );
+    assert_eq!(
+        body["facets"]["prices"],
+        json!([
+            { "min": 0, "max": 1000, "count": 1 },
+            { "min": 1000, "max": 2500, "count": 1 },
+            { "min": 2500, "max": 5000, "count": 0 },
+            { "min": 5000, "max": 10000, "count": 0 },
+            { "min": 10000, "max": null, "count": 0 },
+        ])
+    );
+
+    let body = find(&app, "min_price=1000&max_price=4999").await;
+    assert_eq!(body["total_items"], 3);
+    let prices: Vec<i64> = body["facets"]["prices"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["count"].as_i64().unwrap())
+        .collect();
+    assert_eq!(prices, [1, 2, 1, 0, 1]);
+}
+
+#[tokio::test]
+async fn price_buckets_follow_the_configured_bounds() {
+    let app = TestApp::with_config(|config| config.catalog.price_facet_bounds = vec![2000]).await;
+    catalog(&app).await;
+
+    let body = find(&app, "").await;
+    assert_eq!(
+        body["facets"]["prices"],
+        json!([
+            { "min": 0, "max": 2000, "count": 2 },
+            { "min": 2000, "max": null, "count": 3 },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn prices_go_by_variants_and_stay_within_one_currency() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    let product = |name: &str, price: i64, currency: &str| {
+        ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .price(price)
+            .currency(currency)
+    };
+
+    product("Hammer", 800, "USD").create(&app, admin.id()).await;
+    // Sells from 300 through its cheaper variant, the other one keeps the product's price;
+    let drill = product("Drill", 5000, "USD").create(&app, admin.id()).await;
+    fixtures::variant(&app, drill.id, "S", Some(300), 1, admin.id()).await;
+    fixtures::variant(&app, drill.id, "L", None, 1, admin.id()).await;
+    product("Saw", 2000, "USD").create(&app, admin.id()).await;
+    // 1000 yen, not 10 dollars;
+    product("Shovel", 1000, "JPY")
+        .create(&app, admin.id())
+        .await;
+
+    assert_eq!(names(&app, "max_price=500").await, ["Drill"]);
+    assert_eq!(
+        names(&app, "min_price=500&max_price=1500").await,
+        ["Hammer"]
+    );
+    assert_eq!(
+        names(&app, "currency=jpy&min_price=500&max_price=1500").await,
+        ["Shovel"]
+    );
+
+    // Each currency's products are sorted among themselves;
+    assert_eq!(
+        names(&app, "sort=price_asc").await,
+        ["Shovel", "Drill", "Hammer", "Saw"]
+    );
+    assert_eq!(
+        names(&app, "sort=price_desc").await,
+        ["Shovel", "Saw", "Hammer", "Drill"]
+    );
+    assert_eq!(
+        names(&app, "currency=USD&sort=price_desc").await,
+        ["Saw", "Hammer", "Drill"]
+    );
+
+    // Buckets count the products of a single currency;
+    let body = find(&app, "").await;
+    assert_eq!(body["facets"]["currency"], "USD");
+    let prices: Vec<i64> = body["facets"]["prices"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["count"].as_i64().unwrap())
+        .collect();
+    assert_eq!(prices, [2, 1, 0, 0, 0]);
+
+    let body = find(&app, "currency=JPY").await;
+    assert_eq!(body["facets"]["currency"], "JPY");
+    assert_eq!(body["facets"]["prices"][1]["count"], 1);
+
+    let (status, body) = app.get("/products/find?currency=XYZ", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_CURRENCY");
+}
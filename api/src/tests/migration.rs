@@ -0,0 +1,64 @@
+use sea_orm::{ConnectionTrait, Database, DbBackend, Statement};
+
+use ::migration::{Migrator, MigratorTrait};
+
+#[tokio::test]
+async fn unique_name_indexes_report_names_differing_by_case() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    // Every migration before the unique indexes;
+    Migrator::up(&db, Some(13)).await.unwrap();
+
+    for name in ["Acme", "acme", "Globex", "ACME"] {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            r#"INSERT INTO "brand" ("name", "created_at", "updated_at") VALUES ($1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"#,
+            [name.into()],
+        ))
+        .await
+        .unwrap();
+    }
+
+    let err = Migrator::up(&db, None).await.unwrap_err().to_string();
+    assert!(err.contains("brand.name: ACME, Acme, acme"), "{}", err);
+    assert!(!err.contains("Globex"));
+    assert_eq!(
+        Migrator::get_pending_migrations(&db).await.unwrap().len(),
+        6
+    );
+}
+
+#[tokio::test]
+async fn category_parent_is_dropped_again_on_sqlite() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    // Up to the category tree, with a product in a subcategory;
+    Migrator::up(&db, Some(15)).await.unwrap();
+
+    for sql in [
+        r#"INSERT INTO "category" ("name", "created_at", "updated_at") VALUES ('Tools', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"#,
+        r#"INSERT INTO "category" ("name", "created_at", "updated_at", "parent_id") VALUES ('Hand tools', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, 1)"#,
+        r#"INSERT INTO "brand" ("name", "created_at", "updated_at") VALUES ('Acme', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"#,
+        r#"INSERT INTO "product" ("name", "price", "stock", "category_id", "brand_id", "created_at", "updated_at") VALUES ('Hammer', 1500, 10, 2, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"#,
+    ] {
+        db.execute(Statement::from_string(DbBackend::Sqlite, sql.to_string()))
+            .await
+            .unwrap();
+    }
+
+    Migrator::down(&db, Some(1)).await.unwrap();
+
+    let query =
+        |sql: &str| db.query_all(Statement::from_string(DbBackend::Sqlite, sql.to_string()));
+    // Unquoted, SQLite reads an unknown quoted identifier as a string;
+    assert!(query(r#"SELECT parent_id FROM "category""#).await.is_err());
+    assert_eq!(
+        query(r#"SELECT "id" FROM "category""#).await.unwrap().len(),
+        2
+    );
+    assert!(query("PRAGMA foreign_key_check").await.unwrap().is_empty());
+    // The unique name index is back on the rebuilt table;
+    assert!(query(r#"INSERT INTO "category" ("name", "created_at", "updated_at") VALUES ('TOOLS', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"#)
+        .await
+        .is_err());
+
+    Migrator::up(&db, Some(1)).await.unwrap();
+}
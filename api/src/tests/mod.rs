@@ -0,0 +1,205 @@
+// End-to-end tests, each test gets its own in-memory SQLite database with every migration applied,
+// requests go through the real Router via tower's oneshot so no socket is bound;
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use hyper::body::to_bytes;
+use sea_orm::{Database, DbConn};
+use serde_json::Value;
+use std::{env, fs, path::PathBuf, sync::Arc};
+use tower::ServiceExt;
+
+use ::migration::{Migrator, MigratorTrait};
+
+use crate::{
+    storage::Storage, utils::encryption::generate_random_string, AppBuilder, AppConfig, AppState,
+};
+
+mod fixtures;
+
+mod app;
+mod auth;
+mod brand;
+mod cart;
+mod cascade;
+mod category;
+mod errors;
+mod extractor;
+mod health;
+mod image;
+mod listing;
+mod migration;
+mod openapi;
+mod order;
+mod pagination;
+mod product;
+mod search;
+mod soft_delete;
+mod storage;
+mod variant;
+
+pub struct TestApp {
+    pub db: DbConn,
+    pub config: Arc<AppConfig>,
+    pub storage: Arc<dyn Storage>,
+    router: Router,
+    uploads: PathBuf,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_builder(|builder| builder).await
+    }
+
+    // Lets a test mount extra routes/layers the way an embedding binary would;
+    pub async fn with_builder(customize: impl FnOnce(AppBuilder) -> AppBuilder) -> Self {
+        Self::build(|_| {}, customize).await
+    }
+
+    // Lets a test change settings on top of the test defaults;
+    pub async fn with_config(configure: impl FnOnce(&mut AppConfig)) -> Self {
+        Self::build(configure, |builder| builder).await
+    }
+
+    async fn build(
+        configure: impl FnOnce(&mut AppConfig),
+        customize: impl FnOnce(AppBuilder) -> AppBuilder,
+    ) -> Self {
+        // JWT_KEY is read once through lazy_static, every test uses the same value;
+        env::set_var("JWT_KEY", "test-secret");
+
+        let mut config = AppConfig::default();
+        config.database.url = "sqlite::memory:".to_string();
+        config.auth.bcrypt_cost = 4;
+        // Every test stores its uploads in its own directory, removed when the TestApp is dropped;
+        let uploads =
+            env::temp_dir().join(format!("api-test-uploads-{}", generate_random_string(12)));
+        config.storage.local_dir = uploads.to_string_lossy().into_owned();
+        configure(&mut config);
+
+        let db = Database::connect(config.connect_options())
+            .await
+            .expect("Failed to open test database");
+
+        Migrator::up(&db, None)
+            .await
+            .expect("Failed to migrate test database");
+
+        let state = AppState::new(db.clone())
+            .with_config(config)
+            .expect("Invalid test config");
+        let config = state.config.clone();
+        let storage = state.storage.clone();
+        let router = customize(AppBuilder::new(state)).build();
+
+        Self {
+            db,
+            config,
+            storage,
+            router,
+            uploads,
+        }
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut builder = Request::builder().method(method).uri(uri);
+
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .unwrap();
+
+        let (status, _, body) = self.send(request).await;
+
+        (status, body)
+    }
+
+    // Lower level than request, for tests that need to set or inspect headers;
+    pub async fn send(&self, request: Request<Body>) -> (StatusCode, HeaderMap, Value) {
+        let (status, headers, bytes) = self.send_raw(request).await;
+
+        // Some responses (e.g. a TypedHeader rejection or an unknown route) are not JSON;
+        let body = serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+
+        (status, headers, body)
+    }
+
+    // For responses that are not JSON, e.g. the uploaded files;
+    pub async fn send_raw(&self, request: Request<Body>) -> (StatusCode, HeaderMap, Bytes) {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body()).await.unwrap();
+
+        (status, headers, bytes)
+    }
+
+    // Sends `bytes` as the `file` part of a multipart/form-data body;
+    pub async fn upload(
+        &self,
+        uri: &str,
+        token: Option<&str>,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> (StatusCode, Value) {
+        let boundary = "test-boundary-7MA4YWxkTrZu0gW";
+        let mut body = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"upload\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, content_type
+        )
+        .into_bytes();
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+        let mut builder = Request::builder().method(Method::POST).uri(uri).header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        );
+
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let (status, _, body) = self.send(builder.body(Body::from(body)).unwrap()).await;
+
+        (status, body)
+    }
+
+    pub async fn get(&self, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        self.request(Method::GET, uri, token, None).await
+    }
+
+    pub async fn post(&self, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+        self.request(Method::POST, uri, token, Some(body)).await
+    }
+
+    pub async fn patch(&self, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+        self.request(Method::PATCH, uri, token, Some(body)).await
+    }
+
+    pub async fn delete(&self, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        self.request(Method::DELETE, uri, token, None).await
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.uploads);
+    }
+}
@@ -0,0 +1,114 @@
+use axum::http::StatusCode;
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use utoipa::OpenApi;
+
+use super::TestApp;
+use crate::ApiDoc;
+
+// Scrapes `.route("/path", get(...).post(...))` calls (and their "/prefix" nest) from every file in routes/;
+fn declared_routes() -> Vec<(String, String)> {
+    let nest = Regex::new(r#"\.nest\(\s*"([^"]+)""#).unwrap();
+    let route = Regex::new(r#"\.route\(\s*"([^"]+)","#).unwrap();
+    let method = Regex::new(r"\b(get|post|put|patch|delete)\(").unwrap();
+    let param = Regex::new(r":(\w+)").unwrap();
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/routes");
+    let mut routes = Vec::new();
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        let prefix = nest
+            .captures(&source)
+            .map(|c| c[1].to_string())
+            .unwrap_or_default();
+
+        for r in route.captures_iter(&source) {
+            let path = format!("{}{}", prefix, &r[1]);
+            let path = param.replace_all(&path, "{$1}");
+
+            // The method router runs until the parenthesis closing .route(;
+            let rest = &source[r.get(0).unwrap().end()..];
+            let mut depth = 1;
+            let end = rest
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+
+            for m in method.captures_iter(&rest[..end]) {
+                routes.push((m[1].to_string(), path.to_string()));
+            }
+        }
+    }
+
+    routes
+}
+
+fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                match (key.as_str(), v) {
+                    ("$ref", Value::String(r)) => refs.push(r.clone()),
+                    _ => collect_refs(v, refs),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+        _ => {}
+    }
+}
+
+#[test]
+fn every_route_is_documented() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let routes = declared_routes();
+    assert!(routes.len() > 20, "route scraping found {:?}", routes);
+
+    let missing: Vec<_> = routes
+        .iter()
+        .filter(|(method, path)| spec["paths"][path][method].is_null())
+        .collect();
+
+    assert!(
+        missing.is_empty(),
+        "routes missing from the OpenAPI document, add them to ApiDoc: {:?}",
+        missing
+    );
+}
+
+#[test]
+fn every_schema_reference_resolves() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut refs = Vec::new();
+    collect_refs(&spec, &mut refs);
+
+    for r in refs {
+        let name = r.trim_start_matches("#/components/schemas/");
+        assert!(
+            !spec["components"]["schemas"][name].is_null(),
+            "unresolved schema reference {}",
+            r
+        );
+    }
+}
+
+#[tokio::test]
+async fn openapi_document_is_served() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app.get("/openapi.json", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["openapi"], "3.0.3");
+    assert!(body["paths"]["/orders/{id}/status"]["patch"].is_object());
+}
@@ -0,0 +1,143 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{self, TestUser, UserFixture};
+use super::TestApp;
+
+async fn stock_of(app: &TestApp) -> (i64, i64) {
+    let (_, body) = app.get("/products/find", None).await;
+    let product = &body["data"][0];
+
+    (
+        product["stock"].as_i64().unwrap(),
+        product["reserved_stock"].as_i64().unwrap(),
+    )
+}
+
+async fn place_order(app: &TestApp, customer: &TestUser, product_id: i32, quantity: i32) -> i64 {
+    fixtures::cart(app, customer.id(), product_id, quantity).await;
+
+    let (status, body) = app
+        .post("/orders/checkout", customer.token(), json!({}))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    body["data"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn checkout_with_empty_cart_fails() {
+    let app = TestApp::new().await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, body) = app
+        .post("/orders/checkout", customer.token(), json!({}))
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn checkout_consumes_reservation_and_empties_cart() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let order_id = place_order(&app, &customer, product.id, 3).await;
+
+    assert_eq!(stock_of(&app).await, (7, 0));
+
+    let (_, body) = app.get("/carts/find", customer.token()).await;
+    assert_eq!(body["total_items"], 0);
+
+    let (status, body) = app
+        .get(&format!("/orders/{}", order_id), customer.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "pending");
+    assert_eq!(body["data"]["total"]["amount"], 4500);
+    assert_eq!(body["data"]["items"][0]["quantity"], 3);
+
+    let (_, body) = app.get("/orders/find", customer.token()).await;
+    assert_eq!(body["total_items"], 1);
+}
+
+#[tokio::test]
+async fn customers_cannot_see_other_orders() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let alice = UserFixture::customer("alice").create(&app).await;
+    let bob = UserFixture::customer("bob").create(&app).await;
+
+    let order_id = place_order(&app, &alice, product.id, 1).await;
+
+    let (status, _) = app.get(&format!("/orders/{}", order_id), bob.token()).await;
+    assert_ne!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .get(&format!("/orders/{}", order_id), admin.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn cancelling_pending_order_restores_stock() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let order_id = place_order(&app, &customer, product.id, 4).await;
+
+    let (status, body) = app
+        .patch(
+            &format!("/orders/{}/status", order_id),
+            customer.token(),
+            json!({ "status": "cancelled" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "cancelled");
+
+    assert_eq!(stock_of(&app).await, (10, 0));
+
+    let (status, body) = app
+        .get(&format!("/orders/{}/history", order_id), customer.token())
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn status_transitions_follow_the_state_machine() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let order_id = place_order(&app, &customer, product.id, 1).await;
+    let uri = format!("/orders/{}/status", order_id);
+
+    // Only admins move orders forward;
+    let (status, _) = app
+        .patch(&uri, customer.token(), json!({ "status": "paid" }))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .patch(&uri, admin.token(), json!({ "status": "shipped" }))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    for next in ["paid", "shipped", "delivered"] {
+        let (status, body) = app
+            .patch(&uri, admin.token(), json!({ "status": next }))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], next);
+    }
+
+    let (status, _) = app
+        .patch(&uri, admin.token(), json!({ "status": "cancelled" }))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
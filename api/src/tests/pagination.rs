@@ -0,0 +1,254 @@
+use axum::http::StatusCode;
+use serde_json::Value;
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+
+async fn list(app: &TestApp, uri: &str, token: Option<&str>) -> Value {
+    let (status, body) = app.get(uri, token).await;
+    assert_eq!(status, StatusCode::OK, "{}: {}", uri, body);
+
+    body
+}
+
+fn field(body: &Value, key: &str) -> Vec<String> {
+    body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item[key].to_string().trim_matches('"').to_string())
+        .collect()
+}
+
+// Follows next_cursor from the first page to the last, then prev_cursor back to the first;
+async fn walk(
+    app: &TestApp,
+    uri: &str,
+    key: &str,
+    token: Option<&str>,
+) -> (Vec<String>, Vec<String>) {
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    let mut body = list(app, uri, token).await;
+    assert!(body["prev_cursor"].is_null());
+    let mut forward = field(&body, key);
+
+    while let Some(cursor) = body["next_cursor"].as_str() {
+        body = list(
+            app,
+            &format!("{}{}cursor={}", uri, separator, cursor),
+            token,
+        )
+        .await;
+        forward.extend(field(&body, key));
+    }
+
+    let mut backward = field(&body, key);
+
+    while let Some(cursor) = body["prev_cursor"].as_str() {
+        body = list(
+            app,
+            &format!("{}{}cursor={}", uri, separator, cursor),
+            token,
+        )
+        .await;
+        backward.splice(0..0, field(&body, key));
+    }
+
+    (forward, backward)
+}
+
+#[tokio::test]
+async fn cursors_walk_brands_and_categories_both_ways() {
+    let app = TestApp::new().await;
+    let mut brands = Vec::new();
+    for name in ["Acme", "Bolt", "Crown", "Delta", "Echo"] {
+        brands.push(fixtures::brand(&app, name).await.name);
+        fixtures::category(&app, name).await;
+    }
+
+    let body = list(&app, "/brands/find?size=2", None).await;
+    assert_eq!(field(&body, "name"), ["Acme", "Bolt"]);
+    assert_eq!(body["total_items"], 5);
+    assert_eq!(body["total_page"], 3);
+
+    let body = list(
+        &app,
+        &format!(
+            "/brands/find?size=2&cursor={}",
+            body["next_cursor"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(field(&body, "name"), ["Crown", "Delta"]);
+    assert!(body["prev_cursor"].is_string());
+
+    for uri in ["/brands/find?size=2", "/categories/find?size=2"] {
+        let (forward, backward) = walk(&app, uri, "name", None).await;
+        assert_eq!(forward, brands);
+        assert_eq!(backward, brands);
+    }
+
+    // A page found by number links to its neighbours as well;
+    let body = list(&app, "/brands/find?size=2&page=2", None).await;
+    let body = list(
+        &app,
+        &format!(
+            "/brands/find?size=2&cursor={}",
+            body["prev_cursor"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(field(&body, "name"), ["Acme", "Bolt"]);
+    assert!(body["prev_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn product_cursors_follow_every_sort() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    // Equal prices and names differing only in case, the id has to break the ties;
+    for (name, price) in [
+        ("Hammer", 800),
+        ("saw", 3000),
+        ("Drill", 800),
+        ("hammer drill", 3000),
+        ("Rake", 1500),
+        ("Saw blade", 800),
+        ("drill bit", 1500),
+    ] {
+        ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .price(price)
+            .description("A sturdy tool for the workshop")
+            .create(&app, admin.id())
+            .await;
+    }
+
+    for query in [
+        "",
+        "sort=price_asc",
+        "sort=price_desc",
+        "sort=newest",
+        "sort=name",
+        "search=drill",
+        "search=tool",
+        "max_price=1500&sort=price_desc",
+    ] {
+        let listed = field(
+            &list(&app, &format!("/products/find?size=50&{}", query), None).await,
+            "name",
+        );
+        let (forward, backward) = walk(
+            &app,
+            &format!("/products/find?size=2&{}", query),
+            "name",
+            None,
+        )
+        .await;
+
+        assert_eq!(forward, listed, "{}", query);
+        assert_eq!(backward, listed, "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn carts_are_paged_by_cursor() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    let mut products = Vec::new();
+    for name in ["Hammer", "Saw", "Drill"] {
+        let product = ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .stock(5)
+            .create(&app, admin.id())
+            .await;
+        fixtures::cart(&app, customer.id(), product.id, 1).await;
+        products.push(name.to_string());
+    }
+
+    let (forward, backward) =
+        walk(&app, "/carts/find?size=2", "product_name", customer.token()).await;
+    assert_eq!(forward, products);
+    assert_eq!(backward, products);
+}
+
+#[tokio::test]
+async fn count_can_be_skipped() {
+    let app = TestApp::new().await;
+    fixtures::brand(&app, "Acme").await;
+
+    let body = list(&app, "/brands/find?count=false", None).await;
+    assert!(body["total_items"].is_null());
+    assert!(body["total_page"].is_null());
+    assert_eq!(field(&body, "name"), ["Acme"]);
+
+    let body = list(&app, "/products/find?count=false", None).await;
+    assert!(body["total_items"].is_null());
+}
+
+#[tokio::test]
+async fn size_is_bounded() {
+    let app = TestApp::new().await;
+
+    list(&app, "/brands/find?size=100", None).await;
+
+    for uri in ["/brands/find?size=101", "/products/find?size=1000"] {
+        let (status, body) = app.get(uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_SIZE");
+    }
+}
+
+#[tokio::test]
+async fn foreign_or_tampered_cursors_are_refused() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    fixtures::brand(&app, "Bolt").await;
+    let category = fixtures::category(&app, "Tools").await;
+    for (name, price) in [("Hammer", 100), ("Saw", 200)] {
+        ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .price(price)
+            .create(&app, admin.id())
+            .await;
+    }
+
+    let body = list(&app, "/brands/find?size=1", None).await;
+    let brands = body["next_cursor"].as_str().unwrap().to_string();
+    let body = list(&app, "/products/find?size=1&sort=price_asc", None).await;
+    let products = body["next_cursor"].as_str().unwrap().to_string();
+
+    // The same cursor pointing at another brand, under the original signature;
+    let (payload, signature) = brands.split_once('.').unwrap();
+    let mut decoded: Value =
+        serde_json::from_slice(&base64::decode_config(payload, base64::URL_SAFE_NO_PAD).unwrap())
+            .unwrap();
+    decoded["k"][0]["i"] = 0.into();
+    let forged = format!(
+        "{}.{}",
+        base64::encode_config(decoded.to_string(), base64::URL_SAFE_NO_PAD),
+        signature
+    );
+
+    for uri in [
+        format!("/categories/find?cursor={}", brands),
+        format!("/products/find?sort=name&cursor={}", products),
+        format!("/brands/find?cursor={}", forged),
+        format!("/brands/find?cursor={}x", brands),
+        "/brands/find?cursor=not-a-cursor".to_string(),
+    ] {
+        let (status, body) = app.get(&uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(body["code"], "INVALID_CURSOR", "{}", uri);
+    }
+}
@@ -0,0 +1,310 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+
+#[tokio::test]
+async fn admin_creates_product_with_money_price() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    let (status, _) = app
+        .post(
+            "/products/create",
+            admin.token(),
+            json!({
+                "name": "Hammer",
+                "price": 1999,
+                "stock": 5,
+                "category_id": category.id,
+                "brand_id": brand.id,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = app.get("/products/find?keyword=ham", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 1);
+
+    let product = &body["data"][0];
+    assert_eq!(product["price"]["amount"], 1999);
+    assert_eq!(product["price"]["currency"], "USD");
+    assert_eq!(product["price"]["formatted"], "19.99");
+    assert_eq!(product["available_stock"], 5);
+    assert_eq!(product["brand_name"], "Acme");
+}
+
+#[tokio::test]
+async fn create_product_validates_payload() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/products/create",
+            admin.token(),
+            json!({ "name": "Hammer" }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+}
+
+#[tokio::test]
+async fn product_management_requires_admin() {
+    let app = TestApp::new().await;
+    let (_, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, _) = app
+        .patch(
+            &format!("/products/update/{}", product.id),
+            customer.token(),
+            json!({ "name": "Mallet" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .get(
+            &format!("/products/{}/inventory", product.id),
+            customer.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn update_product_changes_name_and_price() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let (status, _) = app
+        .patch(
+            &format!("/products/update/{}", product.id),
+            admin.token(),
+            json!({ "name": "Mallet", "price": 2500 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["name"], "Mallet");
+    assert_eq!(body["data"][0]["price"]["amount"], 2500);
+}
+
+#[tokio::test]
+async fn product_cannot_be_renamed_to_an_existing_name() {
+    let app = TestApp::new().await;
+    let (admin, hammer) = fixtures::catalog(&app, 10).await;
+    let category = fixtures::category(&app, "Garden").await;
+    let brand = fixtures::brand(&app, "Globex").await;
+    ProductFixture::new(category.id, brand.id)
+        .name("Mallet")
+        .create(&app, admin.id())
+        .await;
+
+    let (status, body) = app
+        .patch(
+            &format!("/products/update/{}", hammer.id),
+            admin.token(),
+            json!({ "name": "mallet" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "DUPLICATE_PRODUCT");
+}
+
+#[tokio::test]
+async fn adjust_stock_is_recorded_in_inventory_ledger() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let (status, body) = app
+        .post(
+            &format!("/products/{}/adjust-stock", product.id),
+            admin.token(),
+            json!({ "quantity": 5, "reason": "restock", "note": "supplier delivery" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["stock"], 15);
+
+    let (status, body) = app
+        .get(
+            &format!("/products/{}/inventory", product.id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], 2);
+
+    let quantities: Vec<i64> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["quantity"].as_i64().unwrap())
+        .collect();
+    assert!(quantities.contains(&10) && quantities.contains(&5));
+}
+
+#[tokio::test]
+async fn adjust_stock_refuses_negative_restocks_and_returns() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/{}/adjust-stock", product.id);
+
+    for reason in ["restock", "return"] {
+        let (status, body) = app
+            .post(
+                &uri,
+                admin.token(),
+                json!({ "quantity": -3, "reason": reason }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_QUANTITY");
+    }
+
+    let (status, body) = app
+        .post(
+            &uri,
+            admin.token(),
+            json!({ "quantity": -3, "reason": "adjustment", "note": "damaged" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["stock"], 7);
+
+    let (_, body) = app
+        .get(
+            &format!("/products/{}/inventory", product.id),
+            admin.token(),
+        )
+        .await;
+    assert_eq!(body["total_items"], 2);
+}
+
+#[tokio::test]
+async fn update_refuses_stock_and_unknown_fields() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/update/{}", product.id);
+
+    let (status, body) = app.patch(&uri, admin.token(), json!({ "stock": 50 })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "INVALID_BODY_TYPE");
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("unknown field `stock`"));
+
+    let (status, _) = app
+        .patch(
+            &uri,
+            admin.token(),
+            json!({ "name": "Mallet", "colour": "red" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["name"], "Hammer");
+    assert_eq!(body["data"][0]["stock"], 10);
+}
+
+#[tokio::test]
+async fn adjust_stock_cannot_go_below_reserved() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+    fixtures::cart(&app, customer.id(), product.id, 8).await;
+
+    let (status, _) = app
+        .post(
+            &format!("/products/{}/adjust-stock", product.id),
+            admin.token(),
+            json!({ "quantity": -5, "reason": "adjustment" }),
+        )
+        .await;
+
+    assert_ne!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn created_product_can_be_priced_in_other_currency() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+    ProductFixture::new(category.id, brand.id)
+        .name("Saw")
+        .price(500)
+        .currency("JPY")
+        .create(&app, admin.id())
+        .await;
+
+    let (_, body) = app.get("/products/find", None).await;
+
+    assert_eq!(body["data"][0]["price"]["currency"], "JPY");
+    assert_eq!(body["data"][0]["price"]["formatted"], "500");
+}
+
+#[tokio::test]
+async fn currency_changes_only_with_a_new_price() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let uri = format!("/products/update/{}", product.id);
+
+    let (status, body) = app
+        .patch(&uri, admin.token(), json!({ "currency": "JPY" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "CURRENCY_CHANGE_WITHOUT_PRICE");
+
+    // Restating the current currency is not a change;
+    let (status, _) = app
+        .patch(&uri, admin.token(), json!({ "currency": "usd" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .patch(
+            &uri,
+            admin.token(),
+            json!({ "currency": "JPY", "price": 2000 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["price"]["currency"], "JPY");
+    assert_eq!(body["data"][0]["price"]["amount"], 2000);
+}
+
+#[tokio::test]
+async fn keyword_wildcards_match_literally() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let brand = fixtures::brand(&app, "Acme").await;
+    let category = fixtures::category(&app, "Tools").await;
+    for name in ["100% Cotton Rag", "1000 Nails", "Wood_Glue", "Wood Glue"] {
+        ProductFixture::new(category.id, brand.id)
+            .name(name)
+            .create(&app, admin.id())
+            .await;
+    }
+
+    let (_, body) = app.get("/products/find?keyword=100%25", None).await;
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "100% Cotton Rag");
+
+    let (_, body) = app.get("/products/find?keyword=WOOD_", None).await;
+    assert_eq!(body["total_items"], 1);
+    assert_eq!(body["data"][0]["name"], "Wood_Glue");
+}
@@ -0,0 +1,181 @@
+use axum::http::StatusCode;
+use serde_json::Value;
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+
+// Two brands and categories, products whose name, description, brand or category mention "steel";
+async fn catalog(app: &TestApp) {
+    let admin = UserFixture::admin("admin").create(app).await;
+    let acme = fixtures::brand(app, "Acme").await;
+    let steelworks = fixtures::brand(app, "Steelworks").await;
+    let tools = fixtures::category(app, "Tools").await;
+    let garden = fixtures::category(app, "Garden").await;
+
+    for fixture in [
+        ProductFixture::new(tools.id, acme.id)
+            .name("Steel Hammer")
+            .description("A claw hammer with a forged steel head"),
+        ProductFixture::new(tools.id, acme.id)
+            .name("Claw Hammer")
+            .description(
+                "Lightweight fiberglass handle, comfortable grip, balanced for long days of framing work \
+                 and finishing jobs around the house, with a polished steel face that drives nails straight",
+            ),
+        ProductFixture::new(garden.id, steelworks.id).name("Rake"),
+        ProductFixture::new(garden.id, acme.id)
+            .name("Watering Can")
+            .description("Holds <5 litres> & pours gently"),
+    ] {
+        fixture.create(app, admin.id()).await;
+    }
+}
+
+async fn search(app: &TestApp, query: &str) -> Vec<Value> {
+    let (status, body) = app
+        .get(&format!("/products/find?search={}", query), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_items"], body["data"].as_array().unwrap().len());
+
+    body["data"].as_array().unwrap().clone()
+}
+
+fn names(products: &[Value]) -> Vec<&str> {
+    products
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn search_covers_description_brand_and_category_ranked_by_field() {
+    let app = TestApp::new().await;
+    catalog(&app).await;
+
+    // A name match outranks a description match, which outranks a brand match;
+    let found = search(&app, "steel").await;
+    assert_eq!(names(&found), vec!["Steel Hammer", "Claw Hammer", "Rake"]);
+
+    let ranks: Vec<f64> = found
+        .iter()
+        .map(|p| p["search"]["rank"].as_f64().unwrap())
+        .collect();
+    assert!(ranks[0] > ranks[1] && ranks[1] > ranks[2], "{:?}", ranks);
+
+    assert_eq!(
+        names(&search(&app, "garden").await),
+        vec!["Rake", "Watering Can"]
+    );
+}
+
+#[tokio::test]
+async fn every_term_must_match_and_terms_are_prefixes() {
+    let app = TestApp::new().await;
+    catalog(&app).await;
+
+    assert_eq!(
+        names(&search(&app, "HAM%20acm").await),
+        vec!["Steel Hammer", "Claw Hammer"]
+    );
+    assert_eq!(names(&search(&app, "ham+garden").await), Vec::<&str>::new());
+    // Operators are separators, they never reach the query;
+    assert_eq!(names(&search(&app, "rake%20%26%20!").await), vec!["Rake"]);
+    // A term only matches the start of a word;
+    assert_eq!(names(&search(&app, "works").await), Vec::<&str>::new());
+    assert_eq!(names(&search(&app, "ging").await), Vec::<&str>::new());
+}
+
+#[tokio::test]
+async fn search_returns_highlighted_snippets() {
+    let app = TestApp::new().await;
+    catalog(&app).await;
+
+    let found = search(&app, "steel").await;
+    assert_eq!(found[0]["search"]["name"], "<mark>Steel</mark> Hammer");
+    assert_eq!(
+        found[0]["search"]["snippet"],
+        "A claw hammer with a forged <mark>steel</mark> head"
+    );
+
+    // Long descriptions are cut around the first match;
+    let snippet = found[1]["search"]["snippet"].as_str().unwrap();
+    assert!(snippet.starts_with("… "), "{}", snippet);
+    assert!(
+        snippet.contains("polished <mark>steel</mark> face"),
+        "{}",
+        snippet
+    );
+
+    // Only the brand matched, there is nothing to excerpt;
+    assert!(found[2]["search"]["snippet"].is_null());
+
+    // The product's own text is escaped;
+    let found = search(&app, "litres").await;
+    assert_eq!(
+        found[0]["search"]["snippet"],
+        "Holds &lt;5 <mark>litres</mark>&gt; &amp; pours gently"
+    );
+
+    // Plain listings carry no search data;
+    let (_, body) = app.get("/products/find", None).await;
+    assert!(body["data"][0].get("search").is_none());
+}
+
+#[tokio::test]
+async fn search_without_words_is_rejected() {
+    let app = TestApp::new().await;
+
+    for query in ["", "%20", "%26%7C!"] {
+        let (status, body) = app
+            .get(&format!("/products/find?search={}", query), None)
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{:?}", query);
+        assert_eq!(body["code"], "INVALID_SEARCH");
+    }
+}
+
+// Ignored by default, run with --ignored once DATABASE_URL points to a Postgres server;
+// It works in a database of its own, dropped afterwards;
+#[cfg(feature = "postgres")]
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at Postgres"]
+async fn postgres_search_matches_word_prefixes_and_forgives_typos() {
+    use sea_orm::{ConnectionTrait, Database, DbBackend, Statement};
+    use std::env;
+
+    use crate::utils::encryption::generate_random_string;
+
+    let url = env::var("DATABASE_URL").expect("DATABASE_URL must point at a Postgres server");
+    let server = Database::connect(&url).await.unwrap();
+    let database = format!("search_test_{}", generate_random_string(12).to_lowercase());
+    let run = |sql: String| server.execute(Statement::from_string(DbBackend::Postgres, sql));
+    run(format!(r#"CREATE DATABASE "{}""#, database))
+        .await
+        .unwrap();
+
+    let (base, _) = url.rsplit_once('/').unwrap();
+    let database_url = format!("{}/{}", base, database);
+    let app = TestApp::with_config(|config| config.database.url = database_url).await;
+    catalog(&app).await;
+
+    let found = search(&app, "steel").await;
+    assert_eq!(names(&found), vec!["Steel Hammer", "Claw Hammer", "Rake"]);
+    assert_eq!(found[0]["search"]["name"], "<mark>Steel</mark> Hammer");
+
+    assert_eq!(
+        names(&search(&app, "HAM%20acm").await),
+        vec!["Steel Hammer", "Claw Hammer"]
+    );
+    assert_eq!(names(&search(&app, "ging").await), Vec::<&str>::new());
+    // The trigram index forgives a typo in the name;
+    assert_eq!(
+        names(&search(&app, "watering%20cann").await),
+        vec!["Watering Can"]
+    );
+
+    drop(app);
+    run(format!(r#"DROP DATABASE "{}" WITH (FORCE)"#, database))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,180 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter, Set,
+};
+use serde_json::json;
+
+use ::entity::{
+    brand, inventory_movement, inventory_movement_archive,
+    prelude::{
+        Brand, Cart, InventoryMovement, InventoryMovementArchive, Product, ProductImage,
+        ProductVariant,
+    },
+    product,
+    soft_delete::SoftDelete,
+};
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+use crate::services::SoftDeleteService;
+
+async fn delete_days_ago(app: &TestApp, model: brand::Model, days: i64) {
+    let mut model = model.into_active_model();
+    model.deleted_at = Set(Some((Utc::now() - Duration::days(days)).into()));
+    model.update(&app.db).await.unwrap();
+}
+
+async fn delete_product_days_ago(app: &TestApp, model: product::Model, days: i64) {
+    let mut model = model.into_active_model();
+    model.deleted_at = Set(Some((Utc::now() - Duration::days(days)).into()));
+    model.update(&app.db).await.unwrap();
+}
+
+#[tokio::test]
+async fn delete_and_restore_keep_entity_errors() {
+    let app = TestApp::new().await;
+    let admin = UserFixture::admin("admin").create(&app).await;
+    let category = fixtures::category(&app, "Tools").await;
+
+    let uri = format!("/categories/restore/{}", category.id);
+    let (status, body) = app.patch(&uri, admin.token(), json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "CATEGORY_NOT_RESTORABLE");
+
+    let uri = format!("/categories/delete/{}", category.id);
+    let (status, _) = app.delete(&uri, admin.token()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.delete(&uri, admin.token()).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "CATEGORY_ALREADY_DELETED");
+}
+
+#[tokio::test]
+async fn deleted_brand_cannot_be_assigned_to_a_product() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let globex = fixtures::brand(&app, "Globex").await;
+    SoftDeleteService::soft_delete::<Brand>(&app.db, globex.id)
+        .await
+        .unwrap();
+
+    let (status, body) = app
+        .patch(
+            &format!("/products/update/{}", product.id),
+            admin.token(),
+            json!({ "brand_id": globex.id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "BRAND_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn purge_removes_expired_rows_nothing_references() {
+    let app = TestApp::new().await;
+    // Acme is still used by the catalog's product;
+    fixtures::catalog(&app, 10).await;
+    let acme = Brand::find().one(&app.db).await.unwrap().unwrap();
+    delete_days_ago(&app, acme, 40).await;
+
+    let old = fixtures::brand(&app, "Old").await;
+    delete_days_ago(&app, old, 40).await;
+    let recent = fixtures::brand(&app, "Recent").await;
+    delete_days_ago(&app, recent, 2).await;
+    fixtures::brand(&app, "Active").await;
+
+    let purged = SoftDeleteService::purge::<Brand>(&app.db, Duration::days(30))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    let mut remaining: Vec<String> = Brand::find()
+        .all(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    remaining.sort();
+    assert_eq!(remaining, ["Acme", "Active", "Recent"]);
+
+    assert_eq!(Brand::find_active().all(&app.db).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn purged_product_takes_its_images_and_variants_along() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, body) = app
+        .upload(
+            &format!("/products/{}/images", product.id),
+            admin.token(),
+            "image/png",
+            &fixtures::png(64, 64),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let url = body["data"]["url"].as_str().unwrap().to_string();
+    fixtures::cart(&app, customer.id(), product.id, 1).await;
+    fixtures::variant(&app, product.id, "HAMMER-XL", Some(1800), 2, admin.id()).await;
+
+    // Sold is in a past order, it is kept like any other referenced row;
+    let sold = ProductFixture::new(product.category_id, product.brand_id)
+        .name("Sold")
+        .create(&app, admin.id())
+        .await;
+    let other = UserFixture::customer("alice").create(&app).await;
+    fixtures::cart(&app, other.id(), sold.id, 1).await;
+    let (status, _) = app.post("/orders/checkout", other.token(), json!({})).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let sold = Product::find_by_id(sold.id)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let purged_id = product.id;
+    delete_product_days_ago(&app, product, 40).await;
+    delete_product_days_ago(&app, sold, 40).await;
+
+    let purged =
+        SoftDeleteService::purge_products(&app.db, app.storage.as_ref(), Duration::days(30))
+            .await
+            .unwrap();
+    assert_eq!(purged, 1);
+
+    let remaining: Vec<String> = Product::find()
+        .all(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(remaining, ["Sold"]);
+    assert_eq!(ProductImage::find().count(&app.db).await.unwrap(), 0);
+    assert_eq!(ProductVariant::find().count(&app.db).await.unwrap(), 0);
+    assert_eq!(Cart::find().count(&app.db).await.unwrap(), 0);
+    // The ledger is append-only, the purged product's history moves to the archive instead of being lost;
+    let ledger = InventoryMovement::find()
+        .filter(inventory_movement::Column::ProductId.eq(purged_id))
+        .count(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(ledger, 0);
+    let archived = InventoryMovementArchive::find()
+        .filter(inventory_movement_archive::Column::ProductId.eq(purged_id))
+        .all(&app.db)
+        .await
+        .unwrap();
+    assert!(!archived.is_empty());
+    assert!(archived
+        .iter()
+        .any(|m| m.variant_id.is_some() && m.quantity == 2));
+
+    let (status, _) = app.get(&url, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
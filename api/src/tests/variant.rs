@@ -0,0 +1,164 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{self, ProductFixture, UserFixture};
+use super::TestApp;
+
+#[tokio::test]
+async fn variants_are_listed_under_their_product() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+
+    let (status, _) = app
+        .post(
+            &format!("/products/{}/variants", product.id),
+            admin.token(),
+            json!({
+                "sku": "SHIRT-M-RED",
+                "options": { "size": "M", "color": "red" },
+                "stock": 4,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    fixtures::variant(&app, product.id, "SHIRT-XL", Some(1800), 2, admin.id()).await;
+
+    let (_, body) = app.get("/products/find", None).await;
+    let variants = &body["data"][0]["variants"];
+    assert_eq!(variants[0]["sku"], "SHIRT-M-RED");
+    assert_eq!(variants[0]["options"]["color"], "red");
+    // Without a price of its own the variant is sold at the product's;
+    assert_eq!(variants[0]["price"]["amount"], 1500);
+    assert_eq!(variants[0]["available_stock"], 4);
+    assert_eq!(variants[1]["price"]["amount"], 1800);
+}
+
+#[tokio::test]
+async fn sku_is_unique_ignoring_case() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    fixtures::variant(&app, product.id, "SHIRT-M", None, 1, admin.id()).await;
+
+    let (status, body) = app
+        .post(
+            &format!("/products/{}/variants", product.id),
+            admin.token(),
+            json!({ "sku": "shirt-m", "options": {}, "stock": 1 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "DUPLICATE_SKU");
+}
+
+#[tokio::test]
+async fn cart_reserves_the_variant_stock() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let variant = fixtures::variant(&app, product.id, "SHIRT-M", None, 2, admin.id()).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/carts/create-or-update",
+            customer.token(),
+            json!({ "product_id": product.id, "quantity": 1 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "VARIANT_REQUIRED");
+
+    // The product's own stock of 10 does not count;
+    let (status, body) = app
+        .post(
+            "/carts/create-or-update",
+            customer.token(),
+            json!({ "product_id": product.id, "variant_id": variant.id, "quantity": 3 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INSUFFICIENT_STOCK");
+
+    let (status, _) = app
+        .post(
+            "/carts/create-or-update",
+            customer.token(),
+            json!({ "product_id": product.id, "variant_id": variant.id, "quantity": 2 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["reserved_stock"], 0);
+    assert_eq!(body["data"][0]["variants"][0]["reserved_stock"], 2);
+
+    let (_, body) = app.get("/carts/find", customer.token()).await;
+    assert_eq!(body["data"][0]["sku"], "SHIRT-M");
+    assert_eq!(body["data"][0]["product_stock"], 2);
+}
+
+#[tokio::test]
+async fn variant_of_another_product_is_not_found() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let other = ProductFixture::new(product.category_id, product.brand_id)
+        .name("Saw")
+        .create(&app, admin.id())
+        .await;
+    let variant = fixtures::variant(&app, other.id, "SAW-L", None, 2, admin.id()).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    let (status, body) = app
+        .post(
+            "/carts/create-or-update",
+            customer.token(),
+            json!({ "product_id": product.id, "variant_id": variant.id, "quantity": 1 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "VARIANT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn checkout_and_cancel_move_the_variant_stock() {
+    let app = TestApp::new().await;
+    let (admin, product) = fixtures::catalog(&app, 10).await;
+    let variant = fixtures::variant(&app, product.id, "SHIRT-XL", Some(1800), 5, admin.id()).await;
+    let customer = UserFixture::customer("bob").create(&app).await;
+
+    app.post(
+        "/carts/create-or-update",
+        customer.token(),
+        json!({ "product_id": product.id, "variant_id": variant.id, "quantity": 2 }),
+    )
+    .await;
+
+    let (status, body) = app
+        .post("/orders/checkout", customer.token(), json!({}))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let order_id = body["data"]["id"].as_i64().unwrap();
+
+    let (_, body) = app
+        .get(&format!("/orders/{}", order_id), customer.token())
+        .await;
+    assert_eq!(body["data"]["total"]["amount"], 3600);
+    assert_eq!(body["data"]["items"][0]["sku"], "SHIRT-XL");
+    assert_eq!(body["data"]["items"][0]["variant_id"], variant.id);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["stock"], 10);
+    assert_eq!(body["data"][0]["variants"][0]["stock"], 3);
+    assert_eq!(body["data"][0]["variants"][0]["reserved_stock"], 0);
+
+    let (status, _) = app
+        .patch(
+            &format!("/orders/{}/status", order_id),
+            customer.token(),
+            json!({ "status": "cancelled" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = app.get("/products/find", None).await;
+    assert_eq!(body["data"][0]["variants"][0]["stock"], 5);
+}
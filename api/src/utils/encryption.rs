@@ -1,13 +1,14 @@
-use bcrypt::DEFAULT_COST;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
 use tokio::sync::oneshot;
 
 use crate::errors::{APIResult, AppError};
 
-pub async fn hash_password(password: String) -> APIResult<String> {
+pub async fn hash_password(password: String, cost: u32) -> APIResult<String> {
     let (tx, rx) = oneshot::channel();
 
     rayon::spawn(move || {
-        let hash = bcrypt::hash(password, DEFAULT_COST);
+        let hash = bcrypt::hash(password, cost);
 
         let _ = tx.send(hash);
     });
@@ -27,3 +28,16 @@ pub async fn validate_password(password_input: String, password_to_cmp: String)
 
     rx.await?.map_err(AppError::BcryptError)
 }
+
+pub fn generate_random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+// Refresh tokens are high-entropy random strings, so a fast SHA-256 digest is enough to avoid storing them in plain text;
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
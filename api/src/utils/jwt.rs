@@ -1,4 +1,3 @@
-use axum::http::StatusCode;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use lazy_static::lazy_static;
@@ -6,10 +5,13 @@ use serde::{Deserialize, Serialize};
 
 use std::env;
 
-use crate::errors::APIResult;
+use ::entity::sea_orm_active_enums::UserRole;
+
+use crate::errors::{APIResult, AppError};
 
 lazy_static! {
-    static ref JWT_KEY: String = env::var("JWT_KEY").expect("JWT_KEY must be set in .env");
+    // Also signs the pagination cursors, see cursor.rs;
+    pub(crate) static ref JWT_KEY: String = env::var("JWT_KEY").expect("JWT_KEY must be set in .env");
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,47 +19,39 @@ pub struct Claims {
     pub exp: i64,
     pub iat: i64,
     pub user_id: i32,
+    pub role: UserRole,
 }
 
 impl Claims {
-    pub fn new(user_id: i32) -> Self {
+    pub fn new(user_id: i32, role: UserRole, ttl: Duration) -> Self {
         Self {
             user_id,
-            exp: (Utc::now() + Duration::hours(24)).timestamp(),
+            role,
+            exp: (Utc::now() + ttl).timestamp(),
             iat: Utc::now().timestamp(),
         }
     }
 }
 
-pub fn generate_token(user_id: i32) -> APIResult<String> {
+// Access tokens are short-lived, clients are expected to renew them through /auth/refresh;
+pub fn generate_token(user_id: i32, role: UserRole, ttl: Duration) -> APIResult<String> {
     let token = jsonwebtoken::encode(
         &Header::default(),
-        &Claims::new(user_id),
+        &Claims::new(user_id, role, ttl),
         &EncodingKey::from_secret(JWT_KEY.as_bytes()),
     )?;
 
     Ok(token)
 }
 
+// Any decoding failure (bad signature, expired, malformed) is the client's token being invalid;
 pub fn verify_token(token: &str) -> APIResult<Claims> {
     let token = jsonwebtoken::decode::<Claims>(
         token,
         &DecodingKey::from_secret(JWT_KEY.as_bytes()),
         &Validation::default(),
-    )?;
+    )
+    .map_err(|_| AppError::InvalidToken)?;
 
     Ok(token.claims)
 }
-
-pub fn verify_token_middleware(token: &str) -> Result<Claims, (StatusCode, &'static str)> {
-    let token = jsonwebtoken::decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(JWT_KEY.as_bytes()),
-        &Validation::default(),
-    );
-
-    match token {
-        Ok(token) => Ok(token.claims),
-        Err(_) => Err((StatusCode::FORBIDDEN, "Invalid token")),
-    }
-}
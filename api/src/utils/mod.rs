@@ -1,2 +1,4 @@
+pub mod cursor;
 pub mod encryption;
 pub mod jwt;
+pub mod money;
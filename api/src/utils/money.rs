@@ -0,0 +1,124 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use utoipa::ToSchema;
+
+use crate::errors::{APIResult, AppError};
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+// ISO-4217 currencies accepted by the API, paired with their number of minor unit digits;
+const CURRENCIES: [(&str, u32); 10] = [
+    ("AUD", 2),
+    ("EUR", 2),
+    ("GBP", 2),
+    ("IDR", 2),
+    ("JPY", 0),
+    ("KRW", 0),
+    ("KWD", 3),
+    ("MYR", 2),
+    ("SGD", 2),
+    ("USD", 2),
+];
+
+// Amounts are kept in minor units (e.g. cents) so no floating point is ever involved;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Money {
+    amount: i64,
+    currency: String,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: impl Into<String>) -> Self {
+        Self {
+            amount,
+            currency: currency.into(),
+        }
+    }
+
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    // Normalizes a client supplied currency code, rejecting anything outside of CURRENCIES;
+    pub fn parse_currency(code: &str) -> APIResult<String> {
+        let code = code.trim().to_uppercase();
+
+        if CURRENCIES.iter().any(|(c, _)| *c == code) {
+            Ok(code)
+        } else {
+            Err(AppError::InvalidCurrency)
+        }
+    }
+
+    pub fn checked_mul(&self, quantity: i32) -> APIResult<Self> {
+        let amount = self
+            .amount
+            .checked_mul(quantity.into())
+            .ok_or(AppError::AmountOverflow)?;
+
+        Ok(Self::new(amount, self.currency.as_str()))
+    }
+
+    pub fn checked_add(&self, other: &Money) -> APIResult<Self> {
+        if self.currency != other.currency {
+            return Err(AppError::CurrencyMismatch);
+        }
+
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or(AppError::AmountOverflow)?;
+
+        Ok(Self::new(amount, self.currency.as_str()))
+    }
+
+    // Renders the amount as a plain decimal string, e.g. 123456 USD -> "1234.56";
+    pub fn to_decimal_string(&self) -> String {
+        let digits = CURRENCIES
+            .iter()
+            .find(|(c, _)| *c == self.currency)
+            .map_or(2, |(_, d)| *d);
+
+        if digits == 0 {
+            return self.amount.to_string();
+        }
+
+        let factor = 10_u64.pow(digits);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let abs = self.amount.unsigned_abs();
+
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            abs / factor,
+            abs % factor,
+            width = digits as usize
+        )
+    }
+}
+
+// Money is serialized by hand, this mirrors that shape for the OpenAPI document;
+#[allow(dead_code)]
+#[derive(ToSchema)]
+#[schema(as = Money)]
+pub struct MoneySchema {
+    /// Amount in the currency's minor unit, e.g. cents for USD
+    amount: i64,
+    /// ISO-4217 currency code
+    currency: String,
+    /// Decimal representation of the amount, e.g. "19.99"
+    formatted: String,
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut money = serializer.serialize_struct("Money", 3)?;
+        money.serialize_field("amount", &self.amount)?;
+        money.serialize_field("currency", &self.currency)?;
+        money.serialize_field("formatted", &self.to_decimal_string())?;
+        money.end()
+    }
+}
@@ -2,15 +2,22 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+use crate::soft_delete::SoftDelete;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(as = Brand)]
 #[sea_orm(table_name = "brand")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub name: String,
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: DateTimeWithTimeZone,
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: DateTimeWithTimeZone,
+    #[schema(value_type = Option<String>, format = DateTime)]
     pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
@@ -27,3 +34,11 @@ impl Related<super::product::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl SoftDelete for Entity {
+    type ActiveModel = ActiveModel;
+
+    fn deleted_at_column() -> Column {
+        Column::DeletedAt
+    }
+}
@@ -10,6 +10,7 @@ pub struct Model {
     pub id: i32,
     pub user_id: i32,
     pub product_id: i32,
+    pub variant_id: Option<i32>,
     pub quantity: i32,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
@@ -25,6 +26,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Product,
+    #[sea_orm(
+        belongs_to = "super::product_variant::Entity",
+        from = "Column::VariantId",
+        to = "super::product_variant::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    ProductVariant,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -47,4 +56,10 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::product_variant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductVariant.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -5,5 +5,16 @@ pub mod prelude;
 pub mod brand;
 pub mod cart;
 pub mod category;
+pub mod inventory_movement;
+pub mod inventory_movement_archive;
+pub mod order;
+pub mod order_item;
+pub mod order_status_history;
 pub mod product;
+pub mod product_image;
+pub mod product_variant;
+pub mod refresh_token;
+pub mod sea_orm_active_enums;
+pub mod soft_delete;
+pub mod stock_reservation;
 pub mod user;
@@ -3,5 +3,14 @@
 pub use super::brand::Entity as Brand;
 pub use super::cart::Entity as Cart;
 pub use super::category::Entity as Category;
+pub use super::inventory_movement::Entity as InventoryMovement;
+pub use super::inventory_movement_archive::Entity as InventoryMovementArchive;
+pub use super::order::Entity as Order;
+pub use super::order_item::Entity as OrderItem;
+pub use super::order_status_history::Entity as OrderStatusHistory;
 pub use super::product::Entity as Product;
+pub use super::product_image::Entity as ProductImage;
+pub use super::product_variant::Entity as ProductVariant;
+pub use super::refresh_token::Entity as RefreshToken;
+pub use super::stock_reservation::Entity as StockReservation;
 pub use super::user::Entity as User;
@@ -3,6 +3,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::soft_delete::SoftDelete;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "product")]
 pub struct Model {
@@ -10,8 +12,10 @@ pub struct Model {
     pub id: i32,
     pub name: String,
     pub description: Option<String>,
-    pub price: i32,
+    pub price: i64,
+    pub currency: String,
     pub stock: i32,
+    pub reserved_stock: i32,
     pub category_id: i32,
     pub brand_id: i32,
     pub created_at: DateTimeWithTimeZone,
@@ -39,6 +43,16 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Category,
+    #[sea_orm(has_many = "super::inventory_movement::Entity")]
+    InventoryMovement,
+    #[sea_orm(has_many = "super::order_item::Entity")]
+    OrderItem,
+    #[sea_orm(has_many = "super::product_image::Entity")]
+    ProductImage,
+    #[sea_orm(has_many = "super::product_variant::Entity")]
+    ProductVariant,
+    #[sea_orm(has_many = "super::stock_reservation::Entity")]
+    StockReservation,
 }
 
 impl Related<super::brand::Entity> for Entity {
@@ -59,4 +73,42 @@ impl Related<super::category::Entity> for Entity {
     }
 }
 
+impl Related<super::inventory_movement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InventoryMovement.def()
+    }
+}
+
+impl Related<super::order_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderItem.def()
+    }
+}
+
+impl Related<super::stock_reservation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockReservation.def()
+    }
+}
+
+impl Related<super::product_image::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductImage.def()
+    }
+}
+
+impl Related<super::product_variant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductVariant.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
+
+impl SoftDelete for Entity {
+    type ActiveModel = ActiveModel;
+
+    fn deleted_at_column() -> Column {
+        Column::DeletedAt
+    }
+}
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+
+// Entities whose rows are hidden by stamping a nullable deleted_at column instead of being removed;
+// The api's SoftDeleteService builds the find/delete/restore/purge operations on top of it;
+pub trait SoftDelete: EntityTrait {
+    type ActiveModel: ActiveModelTrait<Entity = Self> + ActiveModelBehavior + Send;
+
+    // Must be an Option<DateTimeWithTimeZone> column, NULL meaning the row is active;
+    fn deleted_at_column() -> Self::Column;
+
+    fn find_active() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_null())
+    }
+
+    fn find_deleted() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_not_null())
+    }
+
+    fn is_deleted(model: &Self::Model) -> bool {
+        !matches!(
+            model.get(Self::deleted_at_column()),
+            Value::ChronoDateTimeWithTimeZone(None)
+        )
+    }
+}
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.6
 
+use super::sea_orm_active_enums::UserRole;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,7 @@ pub struct Model {
     pub username: String,
     pub email: String,
     pub password: String,
+    pub role: UserRole,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
@@ -19,6 +21,14 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::cart::Entity")]
     Cart,
+    #[sea_orm(has_many = "super::inventory_movement::Entity")]
+    InventoryMovement,
+    #[sea_orm(has_many = "super::order::Entity")]
+    Order,
+    #[sea_orm(has_many = "super::refresh_token::Entity")]
+    RefreshToken,
+    #[sea_orm(has_many = "super::stock_reservation::Entity")]
+    StockReservation,
 }
 
 impl Related<super::cart::Entity> for Entity {
@@ -27,4 +37,28 @@ impl Related<super::cart::Entity> for Entity {
     }
 }
 
+impl Related<super::inventory_movement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InventoryMovement.def()
+    }
+}
+
+impl Related<super::order::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
+
+impl Related<super::refresh_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RefreshToken.def()
+    }
+}
+
+impl Related<super::stock_reservation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockReservation.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
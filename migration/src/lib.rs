@@ -5,6 +5,27 @@ mod m20230103_030859_create_table_categories;
 mod m20230103_133654_create_table_brand;
 mod m20230105_095555_create_product_table;
 mod m20230111_035339_create_cart_table;
+mod m20230116_081522_add_role_to_user;
+mod m20230118_052310_create_refresh_token_table;
+mod m20230124_103045_create_order_table;
+mod m20230124_103112_create_order_item_table;
+mod m20230127_140211_create_order_status_history_table;
+mod m20230130_091730_create_stock_reservation_table;
+mod m20230202_160844_create_inventory_movement_table;
+mod m20230206_112405_convert_prices_to_money;
+mod m20230209_101512_add_unique_name_indexes;
+mod m20230213_094210_add_parent_to_category;
+mod m20230216_143027_create_product_variant_table;
+mod m20230220_102736_create_product_image_table;
+mod m20230223_091544_add_product_search;
+mod m20230227_084150_create_inventory_movement_archive_table;
+
+pub use m20230209_101512_add_unique_name_indexes::{
+    BRAND_NAME_INDEX, CATEGORY_NAME_INDEX, PRODUCT_NAME_INDEX, USER_EMAIL_INDEX,
+    USER_USERNAME_INDEX,
+};
+pub use m20230216_143027_create_product_variant_table::PRODUCT_VARIANT_SKU_INDEX;
+pub use m20230220_102736_create_product_image_table::PRODUCT_IMAGE_PRIMARY_INDEX;
 
 pub struct Migrator;
 
@@ -17,6 +38,20 @@ impl MigratorTrait for Migrator {
             Box::new(m20230103_133654_create_table_brand::Migration),
             Box::new(m20230105_095555_create_product_table::Migration),
             Box::new(m20230111_035339_create_cart_table::Migration),
+            Box::new(m20230116_081522_add_role_to_user::Migration),
+            Box::new(m20230118_052310_create_refresh_token_table::Migration),
+            Box::new(m20230124_103045_create_order_table::Migration),
+            Box::new(m20230124_103112_create_order_item_table::Migration),
+            Box::new(m20230127_140211_create_order_status_history_table::Migration),
+            Box::new(m20230130_091730_create_stock_reservation_table::Migration),
+            Box::new(m20230202_160844_create_inventory_movement_table::Migration),
+            Box::new(m20230206_112405_convert_prices_to_money::Migration),
+            Box::new(m20230209_101512_add_unique_name_indexes::Migration),
+            Box::new(m20230213_094210_add_parent_to_category::Migration),
+            Box::new(m20230216_143027_create_product_variant_table::Migration),
+            Box::new(m20230220_102736_create_product_image_table::Migration),
+            Box::new(m20230223_091544_add_product_search::Migration),
+            Box::new(m20230227_084150_create_inventory_movement_archive_table::Migration),
         ]
     }
 }
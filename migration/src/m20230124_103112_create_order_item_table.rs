@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230105_095555_create_product_table::Product, m20230124_103045_create_order_table::Order,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OrderItem::OrderId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-order-item-order-id")
+                            .from(OrderItem::Table, OrderItem::OrderId)
+                            .to(Order::Table, Order::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(OrderItem::ProductId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-order-item-product-id")
+                            .from(OrderItem::Table, OrderItem::ProductId)
+                            .to(Product::Table, Product::Id),
+                    )
+                    .col(ColumnDef::new(OrderItem::ProductName).string().not_null())
+                    .col(ColumnDef::new(OrderItem::Price).integer().not_null())
+                    .col(ColumnDef::new(OrderItem::Quantity).integer().not_null())
+                    .col(ColumnDef::new(OrderItem::Subtotal).integer().not_null())
+                    .col(
+                        ColumnDef::new(OrderItem::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderItem::Table).to_owned())
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum OrderItem {
+    Table,
+    Id,
+    OrderId,
+    ProductId,
+    ProductName,
+    Price,
+    Quantity,
+    Subtotal,
+    CreatedAt,
+}
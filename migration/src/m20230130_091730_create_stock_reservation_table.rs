@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20220101_000001_create_table::User, m20230105_095555_create_product_table::Product};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(
+                        ColumnDef::new(ProductReservedStock::ReservedStock)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockReservation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockReservation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservation::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stock-reservation-product-id")
+                            .from(StockReservation::Table, StockReservation::ProductId)
+                            .to(Product::Table, Product::Id),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservation::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stock-reservation-user-id")
+                            .from(StockReservation::Table, StockReservation::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservation::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservation::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservation::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-stock-reservation-user-product")
+                    .table(StockReservation::Table)
+                    .col(StockReservation::UserId)
+                    .col(StockReservation::ProductId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-stock-reservation-expires-at")
+                    .table(StockReservation::Table)
+                    .col(StockReservation::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StockReservation::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_column(ProductReservedStock::ReservedStock)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum ProductReservedStock {
+    ReservedStock,
+}
+
+#[derive(Iden)]
+pub enum StockReservation {
+    Table,
+    Id,
+    ProductId,
+    UserId,
+    Quantity,
+    ExpiresAt,
+    CreatedAt,
+}
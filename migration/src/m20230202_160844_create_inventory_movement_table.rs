@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20220101_000001_create_table::User, m20230105_095555_create_product_table::Product};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InventoryMovement::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InventoryMovement::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovement::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-inventory-movement-product-id")
+                            .from(InventoryMovement::Table, InventoryMovement::ProductId)
+                            .to(Product::Table, Product::Id),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovement::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-inventory-movement-user-id")
+                            .from(InventoryMovement::Table, InventoryMovement::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovement::Reason)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovement::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovement::StockAfter)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(InventoryMovement::Note).string().null())
+                    .col(
+                        ColumnDef::new(InventoryMovement::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-inventory-movement-product-id")
+                    .table(InventoryMovement::Table)
+                    .col(InventoryMovement::ProductId)
+                    .col(InventoryMovement::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InventoryMovement::Table).to_owned())
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum InventoryMovement {
+    Table,
+    Id,
+    ProductId,
+    UserId,
+    Reason,
+    Quantity,
+    StockAfter,
+    Note,
+    CreatedAt,
+}
@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DbBackend;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Prices used to be whole currency units stored as integer;
+// They become bigint minor units (cents) with an ISO-4217 currency code next to them;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite integers are already 64 bit and it cannot alter column types anyway;
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for (table, column) in money_columns() {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table)
+                            .modify_column(ColumnDef::new(column).big_integer().not_null())
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        for (table, column) in money_columns() {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(table)
+                        .value(column, Expr::col(column).mul(100))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for table in [Money::Product, Money::Order] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(
+                            ColumnDef::new(Money::Currency)
+                                .string_len(3)
+                                .not_null()
+                                .default("USD"),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [Money::Product, Money::Order] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Money::Currency)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for (table, column) in money_columns() {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(table)
+                        .value(column, Expr::col(column).div(100))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for (table, column) in money_columns() {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table)
+                            .modify_column(ColumnDef::new(column).integer().not_null())
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn money_columns() -> [(Money, Money); 4] {
+    [
+        (Money::Product, Money::Price),
+        (Money::Order, Money::Total),
+        (Money::OrderItem, Money::Price),
+        (Money::OrderItem, Money::Subtotal),
+    ]
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden, Clone, Copy)]
+pub enum Money {
+    Product,
+    Order,
+    OrderItem,
+    Price,
+    Total,
+    Subtotal,
+    Currency,
+}
@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub const USER_USERNAME_INDEX: &str = "idx-user-username-unique";
+pub const USER_EMAIL_INDEX: &str = "idx-user-email-unique";
+pub const CATEGORY_NAME_INDEX: &str = "idx-category-name-unique";
+pub const BRAND_NAME_INDEX: &str = "idx-brand-name-unique";
+pub const PRODUCT_NAME_INDEX: &str = "idx-product-name-unique";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The services used to look for an existing row before inserting, which let duplicates through under concurrency;
+// Uniqueness is now enforced by the database on LOWER(column) so "Acme" and "acme" collide as well;
+// sea-query cannot build expression indexes, the statements below are valid for both Postgres and SQLite;
+// Rows differing only by case are not renamed or merged here, the migration reports them all and stops instead;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        let mut conflicts = Vec::new();
+
+        for (_, table, column) in unique_indexes() {
+            let values = db
+                .query_all(Statement::from_string(
+                    backend,
+                    format!(
+                        r#"SELECT "{column}" AS "value" FROM "{table}" WHERE LOWER("{column}") IN (SELECT LOWER("{column}") FROM "{table}" GROUP BY LOWER("{column}") HAVING COUNT(*) > 1) ORDER BY LOWER("{column}"), "{column}""#,
+                        table = table,
+                        column = column
+                    ),
+                ))
+                .await?
+                .iter()
+                .map(|row| row.try_get::<String>("", "value"))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if !values.is_empty() {
+                conflicts.push(format!("{}.{}: {}", table, column, values.join(", ")));
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(DbErr::Migration(format!(
+                "values differing only by case must be renamed or merged first: {}",
+                conflicts.join("; ")
+            )));
+        }
+
+        for (index, table, column) in unique_indexes() {
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"CREATE UNIQUE INDEX "{}" ON "{}" (LOWER("{}"))"#,
+                    index, table, column
+                ),
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (index, table, _) in unique_indexes() {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(index)
+                        .table(Alias::new(table))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn unique_indexes() -> [(&'static str, &'static str, &'static str); 5] {
+    [
+        (USER_USERNAME_INDEX, "user", "username"),
+        (USER_EMAIL_INDEX, "user", "email"),
+        (CATEGORY_NAME_INDEX, "category", "name"),
+        (BRAND_NAME_INDEX, "brand", "name"),
+        (PRODUCT_NAME_INDEX, "product", "name"),
+    ]
+}
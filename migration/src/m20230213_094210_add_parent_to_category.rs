@@ -0,0 +1,131 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend, DbConn, Statement, TransactionTrait};
+
+use crate::m20230209_101512_add_unique_name_indexes::CATEGORY_NAME_INDEX;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Categories become a tree, a NULL parent_id is a root category;
+// sea-query cannot add a foreign key to an existing SQLite table, the inline REFERENCES below is valid for both backends;
+// Parents are never hard deleted while a child references them, the purge skips them until the child is gone;
+// SQLite cannot drop the column again, down() rebuilds the table there;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"ALTER TABLE "category" ADD COLUMN "parent_id" integer NULL REFERENCES "category" ("id")"#
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-category-parent_id")
+                    .table(Category::Table)
+                    .col(Category::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-category-parent_id")
+                    .table(Category::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        match manager.get_database_backend() {
+            DbBackend::Sqlite => rebuild_without_parent(manager.get_connection()).await,
+            _ => {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Category::Table)
+                            .drop_column(Category::ParentId)
+                            .to_owned(),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+// SQLite cannot drop a column used by a foreign key, the rows are copied aside and the table is recreated without it;
+// Foreign keys are deferred to the commit, by then the products' categories are back under the same ids;
+async fn rebuild_without_parent(db: &DbConn) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+
+    let recreated = Table::create()
+        .table(Category::Table)
+        .col(
+            ColumnDef::new(Category::Id)
+                .integer()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(Category::Name).string().not_null())
+        .col(
+            ColumnDef::new(Category::CreatedAt)
+                .timestamp_with_time_zone()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Category::UpdatedAt)
+                .timestamp_with_time_zone()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Category::DeletedAt)
+                .timestamp_with_time_zone()
+                .null(),
+        )
+        .to_string(SqliteQueryBuilder);
+
+    let columns = r#""id", "name", "created_at", "updated_at", "deleted_at""#;
+
+    for sql in [
+        "PRAGMA defer_foreign_keys = ON".to_string(),
+        format!(
+            r#"CREATE TEMP TABLE "category_copy" AS SELECT {} FROM "category""#,
+            columns
+        ),
+        r#"DROP TABLE "category""#.to_string(),
+        recreated,
+        format!(
+            r#"INSERT INTO "category" ({columns}) SELECT {columns} FROM "category_copy""#,
+            columns = columns
+        ),
+        r#"DROP TABLE "category_copy""#.to_string(),
+        format!(
+            r#"CREATE UNIQUE INDEX "{}" ON "category" (LOWER("name"))"#,
+            CATEGORY_NAME_INDEX
+        ),
+    ] {
+        txn.execute(Statement::from_string(DbBackend::Sqlite, sql))
+            .await?;
+    }
+
+    txn.commit().await
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum Category {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+    ParentId,
+}
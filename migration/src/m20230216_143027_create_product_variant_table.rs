@@ -0,0 +1,158 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use crate::m20230105_095555_create_product_table::Product;
+
+pub const PRODUCT_VARIANT_SKU_INDEX: &str = "idx-product-variant-sku-unique";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// A product sold in several options (size, color...) gets one variant row per combination, each with its own stock;
+// Carts, reservations, the inventory ledger and order items point at the variant, NULL for products without variants;
+// The reservation key becomes (user, product, variant), COALESCE keeps a single row per user and plain product;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductVariant::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProductVariant::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductVariant::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-product-variant-product-id")
+                            .from(ProductVariant::Table, ProductVariant::ProductId)
+                            .to(Product::Table, Product::Id),
+                    )
+                    .col(ColumnDef::new(ProductVariant::Sku).string().not_null())
+                    .col(ColumnDef::new(ProductVariant::Options).json().not_null())
+                    .col(ColumnDef::new(ProductVariant::Price).big_integer().null())
+                    .col(
+                        ColumnDef::new(ProductVariant::Stock)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProductVariant::ReservedStock)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProductVariant::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductVariant::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-product-variant-product-id")
+                    .table(ProductVariant::Table)
+                    .col(ProductVariant::ProductId)
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut statements = vec![format!(
+            r#"CREATE UNIQUE INDEX "{}" ON "product_variant" (LOWER("sku"))"#,
+            PRODUCT_VARIANT_SKU_INDEX
+        )];
+
+        for table in variant_tables() {
+            statements.push(format!(
+                r#"ALTER TABLE "{}" ADD COLUMN "variant_id" integer NULL REFERENCES "product_variant" ("id")"#,
+                table
+            ));
+        }
+
+        statements.push(r#"ALTER TABLE "order_item" ADD COLUMN "sku" varchar NULL"#.to_string());
+        statements.push(r#"DROP INDEX "idx-stock-reservation-user-product""#.to_string());
+        statements.push(
+            r#"CREATE UNIQUE INDEX "idx-stock-reservation-user-product-variant" ON "stock_reservation" ("user_id", "product_id", COALESCE("variant_id", 0))"#
+                .to_string(),
+        );
+
+        for sql in statements {
+            db.execute(Statement::from_string(backend, sql)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        let mut statements = vec![
+            r#"DROP INDEX "idx-stock-reservation-user-product-variant""#.to_string(),
+            r#"CREATE UNIQUE INDEX "idx-stock-reservation-user-product" ON "stock_reservation" ("user_id", "product_id")"#
+                .to_string(),
+            r#"ALTER TABLE "order_item" DROP COLUMN "sku""#.to_string(),
+        ];
+
+        for table in variant_tables() {
+            statements.push(format!(
+                r#"ALTER TABLE "{}" DROP COLUMN "variant_id""#,
+                table
+            ));
+        }
+
+        for sql in statements {
+            db.execute(Statement::from_string(backend, sql)).await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(ProductVariant::Table).to_owned())
+            .await
+    }
+}
+
+fn variant_tables() -> [&'static str; 4] {
+    [
+        "cart",
+        "stock_reservation",
+        "inventory_movement",
+        "order_item",
+    ]
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum ProductVariant {
+    Table,
+    Id,
+    ProductId,
+    Sku,
+    Options,
+    Price,
+    Stock,
+    ReservedStock,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use crate::m20230105_095555_create_product_table::Product;
+
+pub const PRODUCT_IMAGE_PRIMARY_INDEX: &str = "idx-product-image-primary-unique";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The files live in the configured storage backend, a row only keeps their keys and what was checked on upload;
+// Images are listed by position, the partial unique index allows a single primary image per product;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductImage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProductImage::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProductImage::ProductId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-product-image-product-id")
+                            .from(ProductImage::Table, ProductImage::ProductId)
+                            .to(Product::Table, Product::Id),
+                    )
+                    .col(ColumnDef::new(ProductImage::StorageKey).string().not_null())
+                    .col(
+                        ColumnDef::new(ProductImage::ThumbnailKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProductImage::ContentType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProductImage::Size).big_integer().not_null())
+                    .col(ColumnDef::new(ProductImage::Width).integer().not_null())
+                    .col(ColumnDef::new(ProductImage::Height).integer().not_null())
+                    .col(ColumnDef::new(ProductImage::Position).integer().not_null())
+                    .col(
+                        ColumnDef::new(ProductImage::IsPrimary)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ProductImage::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-product-image-product-id")
+                    .table(ProductImage::Table)
+                    .col(ProductImage::ProductId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // sea-query 0.27 cannot build a partial index, this form is valid for both backends;
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(
+                    r#"CREATE UNIQUE INDEX "{}" ON "product_image" ("product_id") WHERE "is_primary""#,
+                    PRODUCT_IMAGE_PRIMARY_INDEX
+                ),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductImage::Table).to_owned())
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum ProductImage {
+    Table,
+    Id,
+    ProductId,
+    StorageKey,
+    ThumbnailKey,
+    ContentType,
+    Size,
+    Width,
+    Height,
+    Position,
+    IsPrimary,
+    CreatedAt,
+}
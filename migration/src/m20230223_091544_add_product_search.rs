@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Full-text search over products: a generated tsvector of the name (weight A) and description (weight B),
+// GIN indexed, plus expression indexes on the brand and category names and a trigram index for typos;
+// The 'simple' configuration does not stem, so names of any language match as typed;
+// SQLite has none of this, product search falls back to LIKE there;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        let db = manager.get_connection();
+
+        for sql in [
+            r#"CREATE EXTENSION IF NOT EXISTS pg_trgm"#,
+            r#"ALTER TABLE "product" ADD COLUMN "search_vector" tsvector GENERATED ALWAYS AS (setweight(to_tsvector('simple', coalesce("name", '')), 'A') || setweight(to_tsvector('simple', coalesce("description", '')), 'B')) STORED"#,
+            r#"CREATE INDEX "idx-product-search-vector" ON "product" USING GIN ("search_vector")"#,
+            r#"CREATE INDEX "idx-product-name-trgm" ON "product" USING GIN ("name" gin_trgm_ops)"#,
+            r#"CREATE INDEX "idx-brand-name-search" ON "brand" USING GIN (to_tsvector('simple', "name"))"#,
+            r#"CREATE INDEX "idx-category-name-search" ON "category" USING GIN (to_tsvector('simple', "name"))"#,
+        ] {
+            db.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        let db = manager.get_connection();
+
+        // The extension is left installed, other schemas of the database may rely on it;
+        for sql in [
+            r#"DROP INDEX "idx-category-name-search""#,
+            r#"DROP INDEX "idx-brand-name-search""#,
+            r#"DROP INDEX "idx-product-name-trgm""#,
+            r#"DROP INDEX "idx-product-search-vector""#,
+            r#"ALTER TABLE "product" DROP COLUMN "search_vector""#,
+        ] {
+            db.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Ledger rows of purged products are moved here, the ledger is append-only and its history must outlive the product;
+// No foreign keys, the product and its variants are gone by then, the ids keep their original values;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InventoryMovementArchive::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::ProductId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::VariantId)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::Reason)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::StockAfter)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::Note)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InventoryMovementArchive::ArchivedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-inventory-movement-archive-product-id")
+                    .table(InventoryMovementArchive::Table)
+                    .col(InventoryMovementArchive::ProductId)
+                    .col(InventoryMovementArchive::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InventoryMovementArchive::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum InventoryMovementArchive {
+    Table,
+    Id,
+    ProductId,
+    VariantId,
+    UserId,
+    Reason,
+    Quantity,
+    StockAfter,
+    Note,
+    CreatedAt,
+    ArchivedAt,
+}